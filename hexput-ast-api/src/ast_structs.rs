@@ -1,63 +1,111 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub start_line: usize,
     pub start_column: usize,
     pub end_line: usize,
     pub end_column: usize,
+    #[serde(flatten, default)]
+    pub span: Span,
 }
 
 impl SourceLocation {
-    pub fn new(start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> Self {
+    pub fn new(start_line: usize, start_column: usize, end_line: usize, end_column: usize, span: Span) -> Self {
         Self {
             start_line,
             start_column,
             end_line,
             end_column,
+            span,
         }
     }
 
-    pub fn from_spans(source_code: &str, start_offset: usize, end_offset: usize) -> Self {
-        let (start_line, start_column) = get_line_column(source_code, start_offset);
-        let (end_line, end_column) = get_line_column(source_code, end_offset);
-        Self::new(start_line, start_column, end_line, end_column)
+    pub fn from_spans(line_index: &LineIndex, start_offset: usize, end_offset: usize) -> Self {
+        line_index.location(start_offset, end_offset)
     }
-}
-
 
-fn get_line_column(source: &str, offset: usize) -> (usize, usize) {
-    let mut line = 1;
-    let mut column = 1;
-    
-    for (i, ch) in source.chars().enumerate() {
-        if i >= offset {
-            break;
-        }
-        
-        if ch == '\n' {
-            line += 1;
-            column = 1;
-        } else {
-            column += 1;
-        }
+    pub fn merge(&self, end: &SourceLocation) -> Self {
+        Self::new(
+            self.start_line,
+            self.start_column,
+            end.end_line,
+            end.end_column,
+            Span::new(self.span.start, end.span.end)
+        )
     }
-    
-    (line, column)
 }
 
-
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnEncoding {
+    Bytes,
+    #[default]
+    Chars,
+    Utf16,
+}
+
+pub struct LineIndex<'a> {
+    source: &'a str,
+    line_starts: Vec<usize>,
+    encoding: ColumnEncoding,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self::with_encoding(source, ColumnEncoding::default())
+    }
+
+    pub fn with_encoding(source: &'a str, encoding: ColumnEncoding) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        
+        Self { source, line_starts, encoding }
+    }
+
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        
+        let line_start = self.line_starts[line];
+        let column = match (self.encoding, self.source.get(line_start..offset)) {
+            (ColumnEncoding::Chars, Some(text)) => text.chars().count(),
+            (ColumnEncoding::Utf16, Some(text)) => text.encode_utf16().count(),
+            _ => offset - line_start,
+        };
+        
+        (line + 1, column + 1)
+    }
+
+    pub fn location(&self, start_offset: usize, end_offset: usize) -> SourceLocation {
+        let (start_line, start_column) = self.line_column(start_offset);
+        let (end_line, end_column) = self.line_column(end_offset);
+        SourceLocation::new(start_line, start_column, end_line, end_column, Span::new(start_offset, end_offset))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Program {
     #[serde(rename = "type")]
     pub node_type: String,
     pub statements: Vec<Statement>,
+    #[serde(default)]
     pub location: SourceLocation,
 }
 
@@ -71,18 +119,20 @@ impl Program {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Statement {
     #[serde(rename = "VARIABLE_DECLARATION")]
     VariableDeclaration {
         name: String,
         value: Expression,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "EXPRESSION_STATEMENT")]
     ExpressionStatement {
         expression: Expression,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "IF_STATEMENT")]
@@ -91,11 +141,13 @@ pub enum Statement {
         body: Block,
         #[serde(skip_serializing_if = "Option::is_none")]
         else_body: Option<Block>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "BLOCK")]
     Block { 
         block: Block,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "CALLBACK_DECLARATION")]
@@ -103,11 +155,13 @@ pub enum Statement {
         name: String,
         params: Vec<String>,
         body: Block,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "RETURN_STATEMENT")]
     ReturnStatement {
         value: Expression,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "LOOP_STATEMENT")]
@@ -115,23 +169,50 @@ pub enum Statement {
         variable: String,
         iterable: Expression,
         body: Block,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "END_STATEMENT")]
     EndStatement {
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "CONTINUE_STATEMENT")]
     ContinueStatement {
+        #[serde(default)]
+        location: SourceLocation,
+    },
+    #[serde(rename = "ERROR_STATEMENT")]
+    ErrorStatement {
+        message: String,
+        #[serde(default)]
         location: SourceLocation,
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Statement {
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            Statement::VariableDeclaration { location, .. } |
+            Statement::ExpressionStatement { location, .. } |
+            Statement::IfStatement { location, .. } |
+            Statement::Block { location, .. } |
+            Statement::CallbackDeclaration { location, .. } |
+            Statement::ReturnStatement { location, .. } |
+            Statement::LoopStatement { location, .. } |
+            Statement::EndStatement { location } |
+            Statement::ContinueStatement { location } |
+            Statement::ErrorStatement { location, .. } => *location,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     #[serde(rename = "type")]
     pub node_type: String,
     pub statements: Vec<Statement>,
+    #[serde(default)]
     pub location: SourceLocation,
 }
 
@@ -145,22 +226,36 @@ impl Block {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Expression {
     #[serde(rename = "STRING_LITERAL")]
     StringLiteral {
         value: String,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "NUMBER_LITERAL")]
     NumberLiteral {
         value: f64,
+        #[serde(default)]
+        location: SourceLocation,
+    },
+    #[serde(rename = "BOOLEAN_LITERAL")]
+    BooleanLiteral {
+        value: bool,
+        #[serde(default)]
+        location: SourceLocation,
+    },
+    #[serde(rename = "NULL_LITERAL")]
+    NullLiteral {
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "IDENTIFIER")]
     Identifier {
         name: String,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "BINARY_EXPRESSION")]
@@ -168,12 +263,29 @@ pub enum Expression {
         left: Box<Expression>,
         operator: Operator,
         right: Box<Expression>,
+        #[serde(default)]
+        location: SourceLocation,
+    },
+    #[serde(rename = "UNARY_EXPRESSION")]
+    UnaryExpression {
+        operator: Operator,
+        operand: Box<Expression>,
+        #[serde(default)]
+        location: SourceLocation,
+    },
+    #[serde(rename = "LOGICAL_EXPRESSION")]
+    LogicalExpression {
+        left: Box<Expression>,
+        operator: Operator,
+        right: Box<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "ASSIGNMENT_EXPRESSION")]
     AssignmentExpression {
         target: String,
         value: Box<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "MEMBER_ASSIGNMENT_EXPRESSION")]
@@ -185,12 +297,14 @@ pub enum Expression {
         property_expr: Option<Box<Expression>>,
         computed: bool,  
         value: Box<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "CALL_EXPRESSION")]
     CallExpression {
         callee: String,
         arguments: Vec<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "MEMBER_CALL_EXPRESSION")]
@@ -202,21 +316,25 @@ pub enum Expression {
         property_expr: Option<Box<Expression>>,
         computed: bool,
         arguments: Vec<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "CALLBACK_REFERENCE")]
     CallbackReference {
         name: String,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "ARRAY_EXPRESSION")]
     ArrayExpression {
         elements: Vec<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "OBJECT_EXPRESSION")]
     ObjectExpression {
         properties: Vec<Property>,
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "MEMBER_EXPRESSION")]
@@ -227,21 +345,48 @@ pub enum Expression {
         #[serde(skip_serializing_if = "Option::is_none")]
         property_expr: Option<Box<Expression>>,
         computed: bool,  
+        #[serde(default)]
         location: SourceLocation,
     },
     #[serde(rename = "KEYS_OF_EXPRESSION")]
     KeysOfExpression {
         object: Box<Expression>,
+        #[serde(default)]
         location: SourceLocation,
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+impl Expression {
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            Expression::StringLiteral { location, .. } |
+            Expression::NumberLiteral { location, .. } |
+            Expression::BooleanLiteral { location, .. } |
+            Expression::NullLiteral { location } |
+            Expression::Identifier { location, .. } |
+            Expression::BinaryExpression { location, .. } |
+            Expression::AssignmentExpression { location, .. } |
+            Expression::MemberAssignmentExpression { location, .. } |
+            Expression::CallExpression { location, .. } |
+            Expression::MemberCallExpression { location, .. } |
+            Expression::CallbackReference { location, .. } |
+            Expression::ArrayExpression { location, .. } |
+            Expression::ObjectExpression { location, .. } |
+            Expression::MemberExpression { location, .. } |
+            Expression::UnaryExpression { location, .. } |
+            Expression::LogicalExpression { location, .. } |
+            Expression::KeysOfExpression { location, .. } => *location,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Property {
     #[serde(rename = "type")]
     pub node_type: String,
     pub key: String,
     pub value: Expression,
+    #[serde(default)]
     pub location: SourceLocation,
 }
 
@@ -256,15 +401,20 @@ impl Property {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Operator {
     Equal,
+    NotEqual,
     Plus,
+    Minus,
     Multiply,
     Divide,
     Greater,
     Less,
     GreaterEqual,
     LessEqual,
+    And,
+    Or,
+    Not,
 }
 
@@ -0,0 +1,81 @@
+use crate::ast_structs::SourceLocation;
+use crate::interpreter::ExecutionError;
+use crate::optimizer::OptimizerWarning;
+use crate::parser::ParseError;
+use crate::semantic::SemanticError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub code: String,
+    pub kind: String,
+    pub message: String,
+    pub location: Option<SourceLocation>,
+    pub token: Option<String>,
+    pub expected: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &str, kind: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            kind: kind.to_string(),
+            message: message.into(),
+            location: None,
+            token: None,
+            expected: Vec::new(),
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        Self {
+            code: error.code().to_string(),
+            kind: error.kind().to_string(),
+            message: error.to_string(),
+            location: Some(error.location()),
+            token: error.token().map(|t| t.to_string()),
+            expected: error.expected().to_vec(),
+        }
+    }
+}
+
+impl From<&ExecutionError> for Diagnostic {
+    fn from(error: &ExecutionError) -> Self {
+        Self {
+            code: error.code().to_string(),
+            kind: error.kind().to_string(),
+            message: error.to_string(),
+            location: Some(error.location()),
+            token: None,
+            expected: Vec::new(),
+        }
+    }
+}
+
+impl From<&SemanticError> for Diagnostic {
+    fn from(error: &SemanticError) -> Self {
+        Self {
+            code: error.code().to_string(),
+            kind: error.kind().to_string(),
+            message: error.to_string(),
+            location: Some(error.location()),
+            token: None,
+            expected: Vec::new(),
+        }
+    }
+}
+
+impl From<&OptimizerWarning> for Diagnostic {
+    fn from(warning: &OptimizerWarning) -> Self {
+        Self {
+            code: warning.code().to_string(),
+            kind: warning.kind().to_string(),
+            message: warning.to_string(),
+            location: Some(warning.location()),
+            token: None,
+            expected: Vec::new(),
+        }
+    }
+}
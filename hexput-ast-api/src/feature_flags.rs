@@ -14,6 +14,10 @@ pub struct FeatureFlags {
     pub allow_array_constructions: bool,
     pub allow_object_constructions: bool,
     pub allow_object_keys: bool,
+    pub allow_logical_operators: bool,
+    pub allow_equality: bool,
+    pub allow_arithmetic_operators: bool,
+    pub allow_comparison_operators: bool,
 }
 
 impl Default for FeatureFlags {
@@ -31,6 +35,10 @@ impl Default for FeatureFlags {
             allow_array_constructions: true,
             allow_object_constructions: true,
             allow_object_keys: true,
+            allow_logical_operators: true,
+            allow_equality: true,
+            allow_arithmetic_operators: true,
+            allow_comparison_operators: true,
         }
     }
 }
@@ -54,6 +62,10 @@ impl FeatureFlags {
             allow_array_constructions: false,
             allow_object_constructions: false,
             allow_object_keys: false,
+            allow_logical_operators: false,
+            allow_equality: false,
+            allow_arithmetic_operators: false,
+            allow_comparison_operators: false,
         }
     }
     
@@ -62,7 +74,40 @@ impl FeatureFlags {
         let mut flags = Self::all_disabled();
         flags.allow_assignments = true;
         flags.allow_object_navigation = true;
+        flags.allow_logical_operators = true;
+        flags.allow_equality = true;
+        flags.allow_arithmetic_operators = true;
+        flags.allow_comparison_operators = true;
         flags
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parses(code: &str) -> bool {
+        crate::process_code(code, FeatureFlags::expressions_only()).is_ok()
+    }
+
+    #[test]
+    fn expressions_only_allows_operators() {
+        assert!(parses("a + 1;"));
+        assert!(parses("a - b * c / d;"));
+        assert!(parses("a == b;"));
+        assert!(parses("a != b;"));
+        assert!(parses("a < b;"));
+        assert!(parses("a && !b || c;"));
+        assert!(parses("a.b = c;"));
+    }
+
+    #[test]
+    fn expressions_only_rejects_statements() {
+        assert!(!parses("vl x = 1;"));
+        assert!(!parses("if a { b; }"));
+        assert!(!parses("loop x in a { b; }"));
+        assert!(!parses("cb f() { b; }"));
+        assert!(!parses("res a;"));
+    }
+}
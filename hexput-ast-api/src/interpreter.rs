@@ -0,0 +1,896 @@
+use crate::ast_structs::{Block, Expression, Operator, Program, SourceLocation, Statement};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Boolean(value) => *value,
+            Value::Number(value) => *value != 0.0 && !value.is_nan(),
+            Value::String(value) => !value.is_empty(),
+            Value::Array(_) | Value::Object(_) => true,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Value::Null | Value::Boolean(_) | Value::Number(_) => 8,
+            Value::String(value) => value.len(),
+            Value::Array(elements) => 8 + elements.iter().map(Value::size).sum::<usize>(),
+            Value::Object(map) => 8 + map.iter().map(|(key, value)| key.len() + value.size()).sum::<usize>(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Number(value) => {
+                if value.fract() == 0.0 && value.abs() < 1e15 {
+                    write!(f, "{}", *value as i64)
+                } else {
+                    write!(f, "{}", value)
+                }
+            },
+            Value::String(value) => write!(f, "{}", value),
+            Value::Array(_) | Value::Object(_) => {
+                let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+                write!(f, "{}", json)
+            },
+        }
+    }
+}
+
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 64;
+pub const MAX_ARRAY_GROWTH: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    pub max_instructions: Option<u64>,
+    pub max_loop_iterations: Option<u64>,
+    pub max_call_depth: Option<usize>,
+    pub max_memory: Option<usize>,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_instructions: None,
+            max_loop_iterations: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_memory: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Limit {
+    Instructions,
+    LoopIterations,
+    CallDepth,
+    Memory,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Instructions => write!(f, "instruction budget"),
+            Limit::LoopIterations => write!(f, "loop iteration cap"),
+            Limit::CallDepth => write!(f, "callback call depth"),
+            Limit::Memory => write!(f, "memory ceiling"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ExecutionError {
+    UndefinedVariable(String, SourceLocation),
+    TypeError(String, SourceLocation),
+    DivisionByZero(SourceLocation),
+    InvalidControlFlow(String, SourceLocation),
+    HostError(String, SourceLocation),
+    LimitExceeded(Limit, SourceLocation),
+    Interrupted(SourceLocation),
+    SyntaxError(String, SourceLocation),
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::UndefinedVariable(name, loc) =>
+                write!(f, "Undefined variable: {} at line {}, column {}",
+                    name, loc.start_line, loc.start_column),
+            ExecutionError::TypeError(msg, loc) =>
+                write!(f, "Type error: {} at line {}, column {}",
+                    msg, loc.start_line, loc.start_column),
+            ExecutionError::DivisionByZero(loc) =>
+                write!(f, "Division by zero at line {}, column {}",
+                    loc.start_line, loc.start_column),
+            ExecutionError::InvalidControlFlow(msg, loc) =>
+                write!(f, "Invalid control flow: {} at line {}, column {}",
+                    msg, loc.start_line, loc.start_column),
+            ExecutionError::HostError(msg, loc) =>
+                write!(f, "Host function error: {} at line {}, column {}",
+                    msg, loc.start_line, loc.start_column),
+            ExecutionError::LimitExceeded(limit, loc) =>
+                write!(f, "Execution limit exceeded: {} at line {}, column {}",
+                    limit, loc.start_line, loc.start_column),
+            ExecutionError::Interrupted(loc) =>
+                write!(f, "Execution interrupted at line {}, column {}",
+                    loc.start_line, loc.start_column),
+            ExecutionError::SyntaxError(msg, loc) =>
+                write!(f, "Syntax error: {} at line {}, column {}",
+                    msg, loc.start_line, loc.start_column),
+        }
+    }
+}
+
+impl ExecutionError {
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            ExecutionError::UndefinedVariable(_, loc) |
+            ExecutionError::TypeError(_, loc) |
+            ExecutionError::DivisionByZero(loc) |
+            ExecutionError::InvalidControlFlow(_, loc) |
+            ExecutionError::HostError(_, loc) |
+            ExecutionError::LimitExceeded(_, loc) |
+            ExecutionError::Interrupted(loc) |
+            ExecutionError::SyntaxError(_, loc) => *loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExecutionError::UndefinedVariable(..) => "E1001",
+            ExecutionError::TypeError(..) => "E1002",
+            ExecutionError::DivisionByZero(..) => "E1003",
+            ExecutionError::InvalidControlFlow(..) => "E1004",
+            ExecutionError::HostError(..) => "E1005",
+            ExecutionError::LimitExceeded(..) => "E1006",
+            ExecutionError::Interrupted(..) => "E1007",
+            ExecutionError::SyntaxError(..) => "E1008",
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExecutionError::UndefinedVariable(..) => "UndefinedVariable",
+            ExecutionError::TypeError(..) => "TypeError",
+            ExecutionError::DivisionByZero(..) => "DivisionByZero",
+            ExecutionError::InvalidControlFlow(..) => "InvalidControlFlow",
+            ExecutionError::HostError(..) => "HostError",
+            ExecutionError::LimitExceeded(..) => "LimitExceeded",
+            ExecutionError::Interrupted(..) => "Interrupted",
+            ExecutionError::SyntaxError(..) => "SyntaxError",
+        }
+    }
+}
+
+pub trait HostFunctions {
+    fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, String>;
+
+    fn call_method(&mut self, _object: Value, method: &str, _arguments: Vec<Value>) -> Result<Value, String> {
+        Err(format!("Unknown method: {}", method))
+    }
+}
+
+impl<F> HostFunctions for F
+where
+    F: FnMut(&str, Vec<Value>) -> Result<Value, String>,
+{
+    fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, String> {
+        self(name, arguments)
+    }
+}
+
+enum Flow {
+    Normal,
+    Return(Value),
+    End(SourceLocation),
+    Continue(SourceLocation),
+}
+
+struct Callback {
+    params: Vec<String>,
+    body: Block,
+}
+
+pub struct Interpreter<'h> {
+    host: &'h mut dyn HostFunctions,
+    globals: HashMap<String, Value>,
+    frames: Vec<Vec<HashMap<String, Value>>>,
+    callbacks: HashMap<String, Rc<Callback>>,
+    limits: ExecutionLimits,
+    instructions: u64,
+    call_depth: usize,
+    memory: usize,
+    interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl<'h> Interpreter<'h> {
+    pub fn new(host: &'h mut dyn HostFunctions) -> Self {
+        Self::with_limits(host, ExecutionLimits::default())
+    }
+
+    pub fn with_limits(host: &'h mut dyn HostFunctions, limits: ExecutionLimits) -> Self {
+        Self {
+            host,
+            globals: HashMap::new(),
+            frames: vec![Vec::new()],
+            callbacks: HashMap::new(),
+            limits,
+            instructions: 0,
+            call_depth: 0,
+            memory: 0,
+            interrupt: None,
+        }
+    }
+
+    pub fn set_interrupt_flag(&mut self, interrupt: Arc<AtomicBool>) {
+        self.interrupt = Some(interrupt);
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.memory += self.measure(&value);
+        if let Some(previous) = self.globals.insert(name.to_string(), value) {
+            self.memory = self.memory.saturating_sub(self.measure(&previous));
+        }
+    }
+
+    pub fn run(&mut self, program: &Program) -> Result<Value, ExecutionError> {
+        match self.execute_statements(&program.statements)? {
+            Flow::Normal => Ok(Value::Null),
+            Flow::Return(value) => Ok(value),
+            Flow::End(location) => Err(ExecutionError::InvalidControlFlow("'end' outside of a loop".to_string(), location)),
+            Flow::Continue(location) => Err(ExecutionError::InvalidControlFlow("'continue' outside of a loop".to_string(), location)),
+        }
+    }
+
+    fn tick(&mut self, location: SourceLocation) -> Result<(), ExecutionError> {
+        if self.interrupt.as_ref().is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            return Err(ExecutionError::Interrupted(location));
+        }
+
+        self.instructions += 1;
+        match self.limits.max_instructions {
+            Some(max) if self.instructions > max => Err(ExecutionError::LimitExceeded(Limit::Instructions, location)),
+            _ => Ok(()),
+        }
+    }
+
+    fn measure(&self, value: &Value) -> usize {
+        if self.limits.max_memory.is_some() { value.size() } else { 0 }
+    }
+
+    fn check_memory(&self, value: &Value, location: SourceLocation) -> Result<(), ExecutionError> {
+        match self.limits.max_memory {
+            Some(max) if self.memory + value.size() > max => Err(ExecutionError::LimitExceeded(Limit::Memory, location)),
+            _ => Ok(()),
+        }
+    }
+
+    fn reserve(&mut self, added: usize, released: usize, location: SourceLocation) -> Result<(), ExecutionError> {
+        self.memory = (self.memory + added).saturating_sub(released);
+        match self.limits.max_memory {
+            Some(max) if self.memory > max => Err(ExecutionError::LimitExceeded(Limit::Memory, location)),
+            _ => Ok(()),
+        }
+    }
+
+    fn release(&mut self, scope: &HashMap<String, Value>) {
+        if self.limits.max_memory.is_some() {
+            let released: usize = scope.values().map(Value::size).sum();
+            self.memory = self.memory.saturating_sub(released);
+        }
+    }
+
+    fn push_scope(&mut self, scope: HashMap<String, Value>, location: SourceLocation) -> Result<(), ExecutionError> {
+        let added = scope.values().map(|value| self.measure(value)).sum();
+        self.current_frame().push(scope);
+        self.reserve(added, 0, location)
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.current_frame().pop() {
+            self.release(&scope);
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut Vec<HashMap<String, Value>> {
+        self.frames.last_mut().expect("interpreter always has an active frame")
+    }
+
+    fn declare(&mut self, name: &str, value: Value, location: SourceLocation) -> Result<(), ExecutionError> {
+        let added = self.measure(&value);
+        let previous = match self.current_frame().last_mut() {
+            Some(scope) => scope.insert(name.to_string(), value),
+            None => self.globals.insert(name.to_string(), value),
+        };
+        let released = previous.map_or(0, |previous| self.measure(&previous));
+        self.reserve(added, released, location)
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.frames.last()
+            .and_then(|frame| frame.iter().rev().find_map(|scope| scope.get(name)))
+            .or_else(|| self.globals.get(name))
+    }
+
+    fn lookup_mut(&mut self, name: &str) -> Option<&mut Value> {
+        let frame = self.frames.last_mut().expect("interpreter always has an active frame");
+        match frame.iter_mut().rev().find(|scope| scope.contains_key(name)) {
+            Some(scope) => scope.get_mut(name),
+            None => self.globals.get_mut(name),
+        }
+    }
+
+    fn execute_block(&mut self, block: &Block) -> Result<Flow, ExecutionError> {
+        self.current_frame().push(HashMap::new());
+        let result = self.execute_statements(&block.statements);
+        self.pop_scope();
+        result
+    }
+
+    fn execute_statements(&mut self, statements: &[Statement]) -> Result<Flow, ExecutionError> {
+        for statement in statements {
+            match self.execute_statement(statement)? {
+                Flow::Normal => {},
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn execute_statement(&mut self, statement: &Statement) -> Result<Flow, ExecutionError> {
+        self.tick(statement.location())?;
+
+        match statement {
+            Statement::VariableDeclaration { name, value, location } => {
+                let value = self.evaluate(value)?;
+                self.declare(name, value, *location)?;
+                Ok(Flow::Normal)
+            },
+            Statement::ExpressionStatement { expression, .. } => {
+                self.evaluate(expression)?;
+                Ok(Flow::Normal)
+            },
+            Statement::IfStatement { condition, body, else_body, .. } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.execute_block(body)
+                } else if let Some(else_body) = else_body {
+                    self.execute_block(else_body)
+                } else {
+                    Ok(Flow::Normal)
+                }
+            },
+            Statement::Block { block, .. } => self.execute_block(block),
+            Statement::CallbackDeclaration { name, params, body, .. } => {
+                self.callbacks.insert(name.clone(), Rc::new(Callback {
+                    params: params.clone(),
+                    body: body.clone(),
+                }));
+                Ok(Flow::Normal)
+            },
+            Statement::ReturnStatement { value, .. } => {
+                let value = self.evaluate(value)?;
+                Ok(Flow::Return(value))
+            },
+            Statement::LoopStatement { variable, iterable, body, location } => {
+                let items = match self.evaluate(iterable)? {
+                    Value::Array(elements) => elements,
+                    Value::Object(map) => map.into_keys().map(Value::String).collect(),
+                    Value::String(text) => text.chars().map(|c| Value::String(c.to_string())).collect(),
+                    other => return Err(ExecutionError::TypeError(
+                        format!("Cannot loop over a value of type {}", other.type_name()),
+                        *location,
+                    )),
+                };
+
+                for (iteration, item) in items.into_iter().enumerate() {
+                    if self.limits.max_loop_iterations.is_some_and(|max| iteration as u64 >= max) {
+                        return Err(ExecutionError::LimitExceeded(Limit::LoopIterations, *location));
+                    }
+
+                    self.push_scope(HashMap::from([(variable.clone(), item)]), *location)?;
+                    let result = self.execute_statements(&body.statements);
+                    self.pop_scope();
+
+                    match result? {
+                        Flow::Normal | Flow::Continue(_) => {},
+                        Flow::End(_) => break,
+                        flow @ Flow::Return(_) => return Ok(flow),
+                    }
+                }
+                Ok(Flow::Normal)
+            },
+            Statement::EndStatement { location } => Ok(Flow::End(*location)),
+            Statement::ContinueStatement { location } => Ok(Flow::Continue(*location)),
+            Statement::ErrorStatement { message, location } => Err(ExecutionError::SyntaxError(message.clone(), *location)),
+        }
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Result<Value, ExecutionError> {
+        self.tick(expression.location())?;
+
+        match expression {
+            Expression::StringLiteral { value, .. } => Ok(Value::String(value.clone())),
+            Expression::NumberLiteral { value, .. } => Ok(Value::Number(*value)),
+            Expression::BooleanLiteral { value, .. } => Ok(Value::Boolean(*value)),
+            Expression::NullLiteral { .. } => Ok(Value::Null),
+            Expression::Identifier { name, location } => self.lookup(name)
+                .cloned()
+                .ok_or_else(|| ExecutionError::UndefinedVariable(name.clone(), *location)),
+            Expression::BinaryExpression { left, operator, right, location } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                let result = apply_binary_operator(operator, left, right, *location)?;
+                self.check_memory(&result, *location)?;
+                Ok(result)
+            },
+            Expression::UnaryExpression { operator, operand, location } => {
+                let operand = self.evaluate(operand)?;
+                match operator {
+                    Operator::Not => Ok(Value::Boolean(!operand.is_truthy())),
+                    Operator::Minus => match operand {
+                        Value::Number(value) => Ok(Value::Number(-value)),
+                        other => Err(ExecutionError::TypeError(
+                            format!("Cannot negate a value of type {}", other.type_name()),
+                            *location,
+                        )),
+                    },
+                    _ => Err(ExecutionError::TypeError(format!("Unsupported unary operator: {:?}", operator), *location)),
+                }
+            },
+            Expression::LogicalExpression { left, operator, right, location } => {
+                let left = self.evaluate(left)?.is_truthy();
+                match operator {
+                    Operator::And if !left => Ok(Value::Boolean(false)),
+                    Operator::Or if left => Ok(Value::Boolean(true)),
+                    Operator::And | Operator::Or => Ok(Value::Boolean(self.evaluate(right)?.is_truthy())),
+                    _ => Err(ExecutionError::TypeError(format!("Unsupported logical operator: {:?}", operator), *location)),
+                }
+            },
+            Expression::AssignmentExpression { target, value, location } => {
+                let value = self.evaluate(value)?;
+                let added = self.measure(&value);
+                let slot = self.lookup_mut(target)
+                    .ok_or_else(|| ExecutionError::UndefinedVariable(target.clone(), *location))?;
+                let previous = std::mem::replace(slot, value.clone());
+                let released = self.measure(&previous);
+                self.reserve(added, released, *location)?;
+                Ok(value)
+            },
+            Expression::MemberAssignmentExpression { object, property, property_expr, value, location, .. } => {
+                let key = self.evaluate_member_key(property, property_expr, *location)?;
+                let mut path = Vec::new();
+                let root = self.resolve_member_path(object, &mut path)?;
+                path.push(key);
+                let value = self.evaluate(value)?;
+
+                let max_memory = self.limits.max_memory;
+                let tracked = max_memory.is_some();
+                let slot = self.lookup_mut(&root)
+                    .ok_or_else(|| ExecutionError::UndefinedVariable(root.clone(), *location))?;
+                let released = if tracked { slot.size() } else { 0 };
+                assign_path(slot, &path, value.clone(), max_memory, *location)?;
+                let added = if tracked { slot.size() } else { 0 };
+                self.reserve(added, released, *location)?;
+                Ok(value)
+            },
+            Expression::CallExpression { callee, arguments, location } => {
+                let arguments = self.evaluate_arguments(arguments)?;
+
+                if let Some(callback) = self.callbacks.get(callee).cloned() {
+                    return self.call_callback(&callback, arguments, *location);
+                }
+
+                self.host.call_function(callee, arguments)
+                    .map_err(|msg| ExecutionError::HostError(msg, *location))
+            },
+            Expression::MemberCallExpression { object, property, property_expr, arguments, location, .. } => {
+                let object = self.evaluate(object)?;
+                let method = match self.evaluate_member_key(property, property_expr, *location)? {
+                    Value::String(name) => name,
+                    other => other.to_string(),
+                };
+                let arguments = self.evaluate_arguments(arguments)?;
+
+                self.host.call_method(object, &method, arguments)
+                    .map_err(|msg| ExecutionError::HostError(msg, *location))
+            },
+            Expression::CallbackReference { name, location } => {
+                if self.callbacks.contains_key(name) {
+                    Ok(Value::String(name.clone()))
+                } else {
+                    Err(ExecutionError::UndefinedVariable(name.clone(), *location))
+                }
+            },
+            Expression::ArrayExpression { elements, location } => {
+                let array = Value::Array(self.evaluate_arguments(elements)?);
+                self.check_memory(&array, *location)?;
+                Ok(array)
+            },
+            Expression::ObjectExpression { properties, location } => {
+                let mut map = BTreeMap::new();
+                for property in properties {
+                    let value = self.evaluate(&property.value)?;
+                    map.insert(property.key.clone(), value);
+                }
+                let object = Value::Object(map);
+                self.check_memory(&object, *location)?;
+                Ok(object)
+            },
+            Expression::MemberExpression { object, property, property_expr, location, .. } => {
+                let object = self.evaluate(object)?;
+                let key = self.evaluate_member_key(property, property_expr, *location)?;
+                get_member(&object, &key, *location)
+            },
+            Expression::KeysOfExpression { object, location } => {
+                match self.evaluate(object)? {
+                    Value::Object(map) => Ok(Value::Array(map.into_keys().map(Value::String).collect())),
+                    Value::Array(elements) => Ok(Value::Array(
+                        (0..elements.len()).map(|i| Value::Number(i as f64)).collect()
+                    )),
+                    other => Err(ExecutionError::TypeError(
+                        format!("Cannot get keys of a value of type {}", other.type_name()),
+                        *location,
+                    )),
+                }
+            },
+        }
+    }
+
+    fn evaluate_arguments(&mut self, expressions: &[Expression]) -> Result<Vec<Value>, ExecutionError> {
+        expressions.iter().map(|expr| self.evaluate(expr)).collect()
+    }
+
+    fn evaluate_member_key(
+        &mut self,
+        property: &Option<String>,
+        property_expr: &Option<Box<Expression>>,
+        location: SourceLocation,
+    ) -> Result<Value, ExecutionError> {
+        match (property, property_expr) {
+            (Some(name), _) => Ok(Value::String(name.clone())),
+            (None, Some(expr)) => self.evaluate(expr),
+            (None, None) => Err(ExecutionError::TypeError("Member access without a property".to_string(), location)),
+        }
+    }
+
+    fn resolve_member_path(&mut self, expression: &Expression, path: &mut Vec<Value>) -> Result<String, ExecutionError> {
+        match expression {
+            Expression::Identifier { name, .. } => Ok(name.clone()),
+            Expression::MemberExpression { object, property, property_expr, location, .. } => {
+                let root = self.resolve_member_path(object, path)?;
+                path.push(self.evaluate_member_key(property, property_expr, *location)?);
+                Ok(root)
+            },
+            _ => Err(ExecutionError::TypeError(
+                "Invalid member assignment target".to_string(),
+                expression.location(),
+            )),
+        }
+    }
+
+    fn call_callback(&mut self, callback: &Callback, arguments: Vec<Value>, location: SourceLocation) -> Result<Value, ExecutionError> {
+        if self.limits.max_call_depth.is_some_and(|max| self.call_depth >= max) {
+            return Err(ExecutionError::LimitExceeded(Limit::CallDepth, location));
+        }
+
+        let mut scope = HashMap::new();
+        let mut arguments = arguments.into_iter();
+        for param in &callback.params {
+            scope.insert(param.clone(), arguments.next().unwrap_or(Value::Null));
+        }
+
+        self.frames.push(Vec::new());
+        self.call_depth += 1;
+        let result = self.push_scope(scope, location)
+            .and_then(|_| self.execute_statements(&callback.body.statements));
+        self.call_depth -= 1;
+        for scope in self.frames.pop().into_iter().flatten() {
+            self.release(&scope);
+        }
+
+        match result? {
+            Flow::Normal => Ok(Value::Null),
+            Flow::Return(value) => Ok(value),
+            Flow::End(location) => Err(ExecutionError::InvalidControlFlow("'end' outside of a loop".to_string(), location)),
+            Flow::Continue(location) => Err(ExecutionError::InvalidControlFlow("'continue' outside of a loop".to_string(), location)),
+        }
+    }
+}
+
+pub fn execute(program: &Program, host: &mut dyn HostFunctions) -> Result<Value, ExecutionError> {
+    Interpreter::new(host).run(program)
+}
+
+pub fn execute_with_limits(program: &Program, host: &mut dyn HostFunctions, limits: ExecutionLimits) -> Result<Value, ExecutionError> {
+    Interpreter::with_limits(host, limits).run(program)
+}
+
+pub(crate) fn apply_binary_operator(operator: &Operator, left: Value, right: Value, location: SourceLocation) -> Result<Value, ExecutionError> {
+    match operator {
+        Operator::Equal => Ok(Value::Boolean(left == right)),
+        Operator::NotEqual => Ok(Value::Boolean(left != right)),
+        Operator::Plus => match (left, right) {
+            (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l + r)),
+            (Value::Array(mut l), Value::Array(r)) => {
+                l.extend(r);
+                Ok(Value::Array(l))
+            },
+            (l @ Value::String(_), r) | (l, r @ Value::String(_)) => Ok(Value::String(format!("{}{}", l, r))),
+            (l, r) => Err(ExecutionError::TypeError(
+                format!("Cannot add {} and {}", l.type_name(), r.type_name()),
+                location,
+            )),
+        },
+        Operator::Minus | Operator::Multiply | Operator::Divide => match (left, right) {
+            (Value::Number(_), Value::Number(r)) if *operator == Operator::Divide && r == 0.0 => {
+                Err(ExecutionError::DivisionByZero(location))
+            },
+            (Value::Number(l), Value::Number(r)) => Ok(Value::Number(match operator {
+                Operator::Minus => l - r,
+                Operator::Multiply => l * r,
+                _ => l / r,
+            })),
+            (l, r) => Err(ExecutionError::TypeError(
+                format!("Arithmetic requires numbers, got {} and {}", l.type_name(), r.type_name()),
+                location,
+            )),
+        },
+        Operator::Greater | Operator::Less | Operator::GreaterEqual | Operator::LessEqual => {
+            let ordering = match (&left, &right) {
+                (Value::Number(l), Value::Number(r)) => l.partial_cmp(r),
+                (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+                _ => return Err(ExecutionError::TypeError(
+                    format!("Cannot compare {} and {}", left.type_name(), right.type_name()),
+                    location,
+                )),
+            };
+            let result = match ordering {
+                Some(ordering) => match operator {
+                    Operator::Greater => ordering.is_gt(),
+                    Operator::Less => ordering.is_lt(),
+                    Operator::GreaterEqual => ordering.is_ge(),
+                    _ => ordering.is_le(),
+                },
+                None => false,
+            };
+            Ok(Value::Boolean(result))
+        },
+        Operator::And | Operator::Or | Operator::Not => Err(ExecutionError::TypeError(
+            format!("Unsupported binary operator: {:?}", operator),
+            location,
+        )),
+    }
+}
+
+fn array_index(key: &Value) -> Option<usize> {
+    match key {
+        Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+        _ => None,
+    }
+}
+
+fn get_member(object: &Value, key: &Value, location: SourceLocation) -> Result<Value, ExecutionError> {
+    match object {
+        Value::Object(map) => Ok(map.get(&key.to_string()).cloned().unwrap_or(Value::Null)),
+        Value::Array(elements) => match key {
+            Value::String(name) if name == "length" => Ok(Value::Number(elements.len() as f64)),
+            _ => Ok(array_index(key).and_then(|i| elements.get(i)).cloned().unwrap_or(Value::Null)),
+        },
+        Value::String(text) => match key {
+            Value::String(name) if name == "length" => Ok(Value::Number(text.chars().count() as f64)),
+            _ => Ok(array_index(key)
+                .and_then(|i| text.chars().nth(i))
+                .map(|c| Value::String(c.to_string()))
+                .unwrap_or(Value::Null)),
+        },
+        other => Err(ExecutionError::TypeError(
+            format!("Cannot read property '{}' of {}", key, other.type_name()),
+            location,
+        )),
+    }
+}
+
+fn assign_path(target: &mut Value, path: &[Value], value: Value, max_memory: Option<usize>, location: SourceLocation) -> Result<(), ExecutionError> {
+    let (key, rest) = match path.split_first() {
+        Some(parts) => parts,
+        None => {
+            *target = value;
+            return Ok(());
+        },
+    };
+
+    let slot = match target {
+        Value::Object(map) if rest.is_empty() => {
+            map.insert(key.to_string(), value);
+            return Ok(());
+        },
+        Value::Object(map) => map.get_mut(&key.to_string()).ok_or_else(|| ExecutionError::TypeError(
+            format!("Cannot set property of missing member '{}'", key),
+            location,
+        ))?,
+        Value::Array(elements) => {
+            let index = array_index(key).ok_or_else(|| ExecutionError::TypeError(
+                format!("Invalid array index: {}", key),
+                location,
+            ))?;
+            if index >= elements.len() {
+                if !rest.is_empty() {
+                    return Err(ExecutionError::TypeError(
+                        format!("Cannot set property of missing element {}", key),
+                        location,
+                    ));
+                }
+                let length = index.checked_add(1)
+                    .filter(|length| length - elements.len() <= MAX_ARRAY_GROWTH)
+                    .ok_or_else(|| ExecutionError::TypeError(
+                        format!("Array index {} is too far past the end of an array of length {}", key, elements.len()),
+                        location,
+                    ))?;
+                let growth = (length - elements.len()).saturating_mul(Value::Null.size());
+                if max_memory.is_some_and(|max| growth > max) {
+                    return Err(ExecutionError::LimitExceeded(Limit::Memory, location));
+                }
+                elements.resize(length, Value::Null);
+            }
+            &mut elements[index]
+        },
+        other => return Err(ExecutionError::TypeError(
+            format!("Cannot set property '{}' of {}", key, other.type_name()),
+            location,
+        )),
+    };
+
+    assign_path(slot, rest, value, max_memory, location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlags;
+
+    fn run(code: &str) -> Result<Value, ExecutionError> {
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses");
+        let mut host = |name: &str, arguments: Vec<Value>| match (name, arguments.as_slice()) {
+            ("double", [Value::Number(n)]) => Ok(Value::Number(n * 2.0)),
+            _ => Err(format!("Unknown function: {}", name)),
+        };
+        execute(&program, &mut host)
+    }
+
+    fn run_with_limits(code: &str, limits: ExecutionLimits) -> Result<Value, ExecutionError> {
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses");
+        let mut host = |name: &str, _: Vec<Value>| Err(format!("Unknown function: {}", name));
+        execute_with_limits(&program, &mut host, limits)
+    }
+
+    #[test]
+    fn arithmetic_follows_precedence() {
+        assert_eq!(run("res 1 + 2 * 3;").unwrap(), Value::Number(7.0));
+        assert_eq!(run("res (1 + 2) * 3;").unwrap(), Value::Number(9.0));
+        assert_eq!(run("res \"a\" + 1;").unwrap(), Value::String("a1".to_string()));
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        assert!(matches!(run("res 1 / 0;"), Err(ExecutionError::DivisionByZero(_))));
+    }
+
+    #[test]
+    fn if_bodies_open_a_scope() {
+        assert_eq!(run("vl x = 1; if x { vl x = 2; } res x;").unwrap(), Value::Number(1.0));
+        assert_eq!(run("vl x = 1; if x { x = 2; } res x;").unwrap(), Value::Number(2.0));
+        assert!(matches!(run("if 1 { vl y = 2; } res y;"), Err(ExecutionError::UndefinedVariable(name, _)) if name == "y"));
+    }
+
+    #[test]
+    fn loops_honour_end_and_continue() {
+        let code = "vl total = 0; loop n in [1, 2, 3, 4] { if n == 2 { continue; } if n == 4 { end; } total = total + n; } res total;";
+        assert_eq!(run(code).unwrap(), Value::Number(4.0));
+        assert!(matches!(run("end;"), Err(ExecutionError::InvalidControlFlow(..))));
+    }
+
+    #[test]
+    fn callbacks_see_params_and_globals_only() {
+        assert_eq!(run("cb add(a, b) { res a + b; } res add(1, 2);").unwrap(), Value::Number(3.0));
+        assert_eq!(run("vl base = 10; cb add(a) { res base + a; } res add(1);").unwrap(), Value::Number(11.0));
+        assert!(matches!(run("cb f() { res y; } if 1 { vl y = 2; res f(); }"), Err(ExecutionError::UndefinedVariable(..))));
+    }
+
+    #[test]
+    fn unknown_calls_go_to_the_host() {
+        assert_eq!(run("res double(21);").unwrap(), Value::Number(42.0));
+        assert!(matches!(run("res triple(1);"), Err(ExecutionError::HostError(..))));
+    }
+
+    #[test]
+    fn array_write_far_past_end_is_rejected() {
+        let result = run("vl a = []; a[99999999999999] = 1;");
+        assert!(matches!(result, Err(ExecutionError::TypeError(..))));
+    }
+
+    #[test]
+    fn array_write_at_max_index_does_not_overflow() {
+        let result = run("vl a = []; a[18446744073709551615] = 1;");
+        assert!(matches!(result, Err(ExecutionError::TypeError(..))));
+    }
+
+    #[test]
+    fn array_growth_is_checked_before_allocating() {
+        let limits = ExecutionLimits { max_memory: Some(1024), ..ExecutionLimits::default() };
+        let result = run_with_limits("vl a = []; a[1000] = 1;", limits);
+        assert!(matches!(result, Err(ExecutionError::LimitExceeded(Limit::Memory, _))));
+    }
+
+    #[test]
+    fn memory_limit_applies_to_all_live_values() {
+        let limits = ExecutionLimits { max_memory: Some(1000), ..ExecutionLimits::default() };
+        let text = "x".repeat(200);
+        let code = format!("vl a = \"{}\"; vl b = a; vl c = a; vl d = a; vl e = a; vl f = a;", text);
+        let result = run_with_limits(&code, limits);
+        assert!(matches!(result, Err(ExecutionError::LimitExceeded(Limit::Memory, _))));
+    }
+
+    #[test]
+    fn memory_is_released_when_scopes_end() {
+        let limits = ExecutionLimits { max_memory: Some(1000), ..ExecutionLimits::default() };
+        let text = "x".repeat(200);
+        let code = format!("cb f() {{ vl a = \"{0}\"; vl b = a; res 1; }} loop i in [1, 2, 3, 4, 5, 6, 7, 8] {{ vl c = \"{0}\"; f(); }}", text);
+        assert!(run_with_limits(&code, limits).is_ok());
+    }
+
+    #[test]
+    fn array_write_just_past_end_grows_array() {
+        let result = run("vl a = [1]; a[3] = 4; res a;");
+        assert_eq!(result.unwrap(), Value::Array(vec![
+            Value::Number(1.0),
+            Value::Null,
+            Value::Null,
+            Value::Number(4.0),
+        ]));
+    }
+
+    #[test]
+    fn bare_blocks_scope_like_if_bodies() {
+        assert_eq!(run("vl x = 1; { vl x = 2; } res x;").unwrap(), Value::Number(1.0));
+        assert_eq!(run("vl x = 1; if true { vl x = 2; } res x;").unwrap(), Value::Number(1.0));
+    }
+
+    #[test]
+    fn default_limits_stop_unbounded_recursion() {
+        let result = run("cb f() { res f(); } res f();");
+        assert!(matches!(result, Err(ExecutionError::LimitExceeded(Limit::CallDepth, _))));
+    }
+}
@@ -1,8 +1,9 @@
 use logos::{Logos, Lexer, Span};
-use crate::ast_structs::SourceLocation;
+use crate::ast_structs::{LineIndex, SourceLocation};
+use std::fmt;
 
 #[derive(Logos, Debug, PartialEq, Clone)]
-#[logos(skip r"[ \t\n\f]+", error = TokenError)]
+#[logos(skip r"[ \t\r\n\f]+", error = TokenError)]
 pub enum Token {
     // Keywords
     #[token("vl")]
@@ -35,19 +36,30 @@ pub enum Token {
     #[token("keysof")]
     KeysOf,
     
+    #[token("true", |_| true)]
+    #[token("false", |_| false)]
+    BooleanLiteral(bool),
+    
+    #[token("null")]
+    Null,
+    
     // Identifiers
     #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_owned(), priority = 2)]
     Identifier(String),
 
     // Comments
-    #[regex(r"//[^\n]*", logos::skip)]
-    Comment,
+    #[regex(r"//[^\n]*", |lex| lex.slice().to_owned())]
+    Comment(String),
     
     // Literals
     #[regex(r#""([^"\\]|\\.)*""#, string_literal)]
+    #[regex(r#""([^"\\]|\\.)*\\?"#, |_| Err(TokenError::UnterminatedString))]
     StringLiteral(String),
     
-    #[regex(r"-?[0-9]+(\.[0-9]+)?", |lex| lex.slice().parse::<f64>().ok())]
+    #[regex(r"[0-9]+(\.[0-9]+)?", number_literal)]
+    #[regex(r"[0-9]+(\.[0-9]+)?[a-zA-Z_][a-zA-Z0-9_]*", |_| Err(TokenError::MalformedNumber))]
+    #[regex(r"[0-9]+\.[0-9]+(\.[0-9]*)+", |_| Err(TokenError::MalformedNumber))]
+    #[regex(r"[0-9]+\.", |_| Err(TokenError::MalformedNumber))]
     NumberLiteral(f64),
     
     // Operators
@@ -57,9 +69,15 @@ pub enum Token {
     #[token("==")]
     EqualEqual,
     
+    #[token("!=")]
+    NotEqual,
+    
     #[token("+")]
     Plus,
     
+    #[token("-")]
+    Minus,
+    
     #[token("*")]
     Multiply,
     
@@ -79,6 +97,16 @@ pub enum Token {
     #[token("<")]
     Less,
     
+    // Logical operators
+    #[token("&&")]
+    And,
+    
+    #[token("||")]
+    Or,
+    
+    #[token("!")]
+    Not,
+    
     // Delimiters
     #[token("{")]
     OpenBrace,
@@ -112,10 +140,68 @@ pub enum Token {
     Dot,
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lexeme = match self {
+            Token::Identifier(name) => return write!(f, "identifier '{}'", name),
+            Token::StringLiteral(value) => return write!(f, "string {:?}", value),
+            Token::NumberLiteral(value) => return write!(f, "number {}", value),
+            Token::BooleanLiteral(value) => return write!(f, "'{}'", value),
+            Token::Comment(_) => return write!(f, "comment"),
+            Token::Vl => "vl",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::Cb => "cb",
+            Token::Res => "res",
+            Token::Loop => "loop",
+            Token::In => "in",
+            Token::End => "end",
+            Token::Continue => "continue",
+            Token::KeysOf => "keysof",
+            Token::Null => "null",
+            Token::Equal => "=",
+            Token::EqualEqual => "==",
+            Token::NotEqual => "!=",
+            Token::Plus => "+",
+            Token::Minus => "-",
+            Token::Multiply => "*",
+            Token::Divide => "/",
+            Token::GreaterEqual => ">=",
+            Token::LessEqual => "<=",
+            Token::Greater => ">",
+            Token::Less => "<",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Not => "!",
+            Token::OpenBrace => "{",
+            Token::CloseBrace => "}",
+            Token::OpenParen => "(",
+            Token::CloseParen => ")",
+            Token::Comma => ",",
+            Token::Semicolon => ";",
+            Token::OpenBracket => "[",
+            Token::CloseBracket => "]",
+            Token::Colon => ":",
+            Token::Dot => ".",
+        };
+        write!(f, "'{}'", lexeme)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct TokenError;
+pub enum TokenError {
+    #[default]
+    UnknownCharacter,
+    UnterminatedString,
+    InvalidEscape(char),
+    MalformedNumber,
+}
+
+fn number_literal(lex: &mut Lexer<Token>) -> Result<f64, TokenError> {
+    lex.slice().parse::<f64>().map_err(|_| TokenError::MalformedNumber)
+}
 
-fn string_literal(lex: &mut Lexer<Token>) -> Option<String> {
+fn string_literal(lex: &mut Lexer<Token>) -> Result<String, TokenError> {
     let slice = lex.slice();
     
     let content = &slice[1..slice.len() - 1];
@@ -132,11 +218,7 @@ fn string_literal(lex: &mut Lexer<Token>) -> Option<String> {
                     'r' => processed.push('\r'),
                     '\\' => processed.push('\\'),
                     '"' => processed.push('"'),
-                    _ => {
-                        // Invalid escape sequence
-                        processed.push('\\');
-                        processed.push(next);
-                    }
+                    _ => return Err(TokenError::InvalidEscape(next)),
                 }
             }
         } else {
@@ -144,7 +226,7 @@ fn string_literal(lex: &mut Lexer<Token>) -> Option<String> {
         }
     }
     
-    Some(processed)
+    Ok(processed)
 }
 
 pub struct TokenWithSpan {
@@ -153,23 +235,80 @@ pub struct TokenWithSpan {
 }
 
 impl TokenWithSpan {
-    pub fn get_location(&self, source_code: &str) -> SourceLocation {
-        SourceLocation::from_spans(source_code, self.span.start, self.span.end)
+    pub fn get_location(&self, line_index: &LineIndex) -> SourceLocation {
+        line_index.location(self.span.start, self.span.end)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicalError {
+    pub error: TokenError,
+    pub span: Span,
+    pub text: String,
+}
+
+impl LexicalError {
+    pub fn get_location(&self, line_index: &LineIndex) -> SourceLocation {
+        line_index.location(self.span.start, self.span.end)
     }
 }
 
-pub fn tokenize(source: &str) -> Vec<TokenWithSpan> {
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            TokenError::UnknownCharacter => write!(f, "Unknown character '{}'", self.text),
+            TokenError::UnterminatedString => write!(f, "Unterminated string literal"),
+            TokenError::InvalidEscape(c) => write!(f, "Invalid escape sequence '\\{}'", c),
+            TokenError::MalformedNumber => write!(f, "Malformed number '{}'", self.text),
+        }
+    }
+}
+
+pub fn tokenize(source: &str) -> Result<Vec<TokenWithSpan>, LexicalError> {
+    let (tokens, mut errors) = tokenize_with_errors(source);
+    
+    if errors.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(errors.remove(0))
+    }
+}
+
+pub fn tokenize_with_errors(source: &str) -> (Vec<TokenWithSpan>, Vec<LexicalError>) {
     let mut lexer = Token::lexer(source);
     let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    
+    while let Some(token) = lexer.next() {
+        match token {
+            Ok(Token::Comment(_)) => {},
+            Ok(token) => tokens.push(TokenWithSpan {
+                token,
+                span: lexer.span(),
+            }),
+            Err(error) => errors.push(LexicalError {
+                error,
+                span: lexer.span(),
+                text: lexer.slice().to_string(),
+            }),
+        }
+    }
+    
+    (tokens, errors)
+}
+
+pub fn tokenize_comments(source: &str) -> Vec<TokenWithSpan> {
+    let mut lexer = Token::lexer(source);
+    let mut comments = Vec::new();
     
     while let Some(token) = lexer.next() {
-        if let Ok(token) = token {
-            tokens.push(TokenWithSpan {
-                token: token.clone(),
+        if let Ok(token @ Token::Comment(_)) = token {
+            comments.push(TokenWithSpan {
+                token,
                 span: lexer.span(),
             });
         }
     }
     
-    tokens
+    comments
 }
@@ -4,22 +4,83 @@ pub mod parser;
 pub mod optimizer;
 pub mod feature_flags;
 pub mod parallel;
+pub mod interpreter;
+pub mod diagnostics;
+pub mod visitor;
+pub mod printer;
+pub mod semantic;
+pub mod manifest;
 
 use serde_json::{to_string_pretty, to_string, Value};
+use ast_structs::{ColumnEncoding, LineIndex};
 use feature_flags::FeatureFlags;
+use optimizer::OptimizerWarning;
 use parser::ParseError;
 
 pub fn process_code(code: &str, feature_flags: FeatureFlags) -> Result<ast_structs::Program, ParseError> {
+    process_code_with_encoding(code, feature_flags, ColumnEncoding::default())
+}
+
+pub fn process_code_with_encoding(code: &str, feature_flags: FeatureFlags, encoding: ColumnEncoding) -> Result<ast_structs::Program, ParseError> {
+    process_code_with_warnings(code, feature_flags, encoding).map(|(program, _)| program)
+}
+
+pub fn process_code_with_warnings(code: &str, feature_flags: FeatureFlags, encoding: ColumnEncoding) -> Result<(ast_structs::Program, Vec<OptimizerWarning>), ParseError> {
     let runtime = parallel::create_runtime();
     
-    let tokens = lexer::tokenize(code);
+    let tokens = lexer::tokenize(code)
+        .map_err(|e| ParseError::LexError(e.to_string(), e.text.clone(), e.get_location(&LineIndex::with_encoding(code, encoding))))?;
+    
+    let mut parser = parser::Parser::with_column_encoding(&tokens, feature_flags, code, encoding);
+    let ast = parser.parse_program()?;
+    
+    Ok(optimizer::optimize_ast_with_warnings(ast, &runtime))
+}
+
+pub fn format_source(code: &str, feature_flags: FeatureFlags) -> Result<String, ParseError> {
+    let line_index = LineIndex::new(code);
+    
+    let tokens = lexer::tokenize(code)
+        .map_err(|e| ParseError::LexError(e.to_string(), e.text.clone(), e.get_location(&line_index)))?;
     
     let mut parser = parser::Parser::new(&tokens, feature_flags, code);
     let ast = parser.parse_program()?;
     
+    let comments = lexer::tokenize_comments(code)
+        .into_iter()
+        .filter_map(|comment| match &comment.token {
+            lexer::Token::Comment(text) => Some((text.trim_end().to_string(), comment.get_location(&line_index))),
+            _ => None,
+        })
+        .collect();
+    
+    Ok(printer::to_source_with_comments(&ast, code, comments))
+}
+
+pub fn process_code_with_diagnostics(code: &str, feature_flags: FeatureFlags, encoding: ColumnEncoding) -> (ast_structs::Program, Vec<ParseError>) {
+    let runtime = parallel::create_runtime();
+    
+    let (ast, errors) = parse_code_with_diagnostics(code, feature_flags, encoding);
+    
     let optimized_ast = optimizer::optimize_ast(ast, &runtime);
     
-    Ok(optimized_ast)
+    (optimized_ast, errors)
+}
+
+pub fn parse_code_with_diagnostics(code: &str, feature_flags: FeatureFlags, encoding: ColumnEncoding) -> (ast_structs::Program, Vec<ParseError>) {
+    let (tokens, lex_errors) = lexer::tokenize_with_errors(code);
+    
+    let mut parser = parser::Parser::with_column_encoding(&tokens, feature_flags, code, encoding);
+    let (ast, parse_errors) = parser.parse_program_with_diagnostics();
+    
+    let line_index = LineIndex::with_encoding(code, encoding);
+    let mut errors: Vec<ParseError> = lex_errors.into_iter()
+        .map(|e| ParseError::LexError(e.to_string(), e.text.clone(), e.get_location(&line_index)))
+        .chain(parse_errors)
+        .collect();
+    errors.sort_by_key(|e| e.location().span.start);
+    
+    (ast, errors)
 }
 
 pub fn filter_locations(value: Value) -> Value {
@@ -61,10 +122,16 @@ pub fn to_json_string_pretty(value: &impl serde::Serialize, include_source_mappi
 }
 
 pub fn format_error_as_json(error: &ParseError, minify: bool) -> String {
+    let diagnostic = diagnostics::Diagnostic::from(error);
     let error_json = serde_json::json!({
         "error": {
             "type": "ParseError",
-            "message": format!("{}", error)
+            "code": diagnostic.code,
+            "kind": diagnostic.kind,
+            "message": diagnostic.message,
+            "location": diagnostic.location,
+            "token": diagnostic.token,
+            "expected": diagnostic.expected
         }
     });
     
@@ -74,3 +141,22 @@ pub fn format_error_as_json(error: &ParseError, minify: bool) -> String {
         to_string_pretty(&error_json).unwrap_or_else(|_| String::from(r#"{"error":{"type":"ParseError","message":"JSON serialization error"}}"#))
     }
 }
+
+pub fn format_warning_as_json(warning: &OptimizerWarning, minify: bool) -> String {
+    let diagnostic = diagnostics::Diagnostic::from(warning);
+    let warning_json = serde_json::json!({
+        "warning": {
+            "type": "OptimizerWarning",
+            "code": diagnostic.code,
+            "kind": diagnostic.kind,
+            "message": diagnostic.message,
+            "location": diagnostic.location
+        }
+    });
+    
+    if minify {
+        to_string(&warning_json).unwrap_or_else(|_| String::from(r#"{"warning":{"type":"OptimizerWarning","message":"JSON serialization error"}}"#))
+    } else {
+        to_string_pretty(&warning_json).unwrap_or_else(|_| String::from(r#"{"warning":{"type":"OptimizerWarning","message":"JSON serialization error"}}"#))
+    }
+}
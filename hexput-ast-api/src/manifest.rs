@@ -0,0 +1,105 @@
+use crate::ast_structs::{Expression, Program, Statement};
+use crate::semantic::{self, SemanticError};
+use crate::visitor::{self, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub host_functions: Vec<String>,
+    pub host_methods: Vec<String>,
+    pub dynamic_member_calls: bool,
+    pub free_variables: Vec<String>,
+    pub callbacks: Vec<CallbackSignature>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackSignature {
+    pub name: String,
+    pub params: Vec<String>,
+}
+
+pub fn extract_manifest(program: &Program) -> Manifest {
+    let mut host_functions = BTreeSet::new();
+    let mut free_variables = BTreeSet::new();
+
+    for error in semantic::analyze_scopes(program, &[]) {
+        match error {
+            SemanticError::UndefinedCallback(name, _) => {
+                host_functions.insert(name);
+            },
+            SemanticError::UndefinedVariable(name, _) |
+            SemanticError::AssignmentBeforeDeclaration(name, _) => {
+                free_variables.insert(name);
+            },
+            SemanticError::Shadowing(..) => {},
+        }
+    }
+
+    let mut collector = ManifestCollector::default();
+    collector.visit_program(program);
+
+    Manifest {
+        host_functions: host_functions.into_iter().collect(),
+        host_methods: collector.host_methods.into_iter().collect(),
+        dynamic_member_calls: collector.dynamic_member_calls,
+        free_variables: free_variables.into_iter().collect(),
+        callbacks: collector.callbacks,
+    }
+}
+
+#[derive(Default)]
+struct ManifestCollector {
+    host_methods: BTreeSet<String>,
+    dynamic_member_calls: bool,
+    callbacks: Vec<CallbackSignature>,
+}
+
+impl Visitor for ManifestCollector {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let Statement::CallbackDeclaration { name, params, .. } = statement {
+            self.callbacks.push(CallbackSignature {
+                name: name.clone(),
+                params: params.clone(),
+            });
+        }
+        visitor::walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        if let Expression::MemberCallExpression { property, property_expr, .. } = expression {
+            match (property, property_expr.as_deref()) {
+                (Some(method), _) |
+                (None, Some(Expression::StringLiteral { value: method, .. })) => {
+                    self.host_methods.insert(method.clone());
+                },
+                _ => self.dynamic_member_calls = true,
+            }
+        }
+        visitor::walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlags;
+
+    fn manifest(code: &str) -> Manifest {
+        extract_manifest(&crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses"))
+    }
+
+    #[test]
+    fn string_literal_method_keys_are_host_methods() {
+        let manifest = manifest(r#"o["trim"](); o["up" + "per"]();"#);
+        assert_eq!(manifest.host_methods, vec!["trim".to_string(), "upper".to_string()]);
+        assert!(!manifest.dynamic_member_calls);
+    }
+
+    #[test]
+    fn computed_method_keys_are_flagged() {
+        let manifest = manifest("vl k = \"trim\"; o[k]();");
+        assert!(manifest.host_methods.is_empty());
+        assert!(manifest.dynamic_member_calls);
+    }
+}
@@ -1,35 +1,93 @@
-use crate::ast_structs::{Block, Expression, Program, Property, Statement};
+use crate::ast_structs::{Block, Expression, Operator, Program, SourceLocation, Statement};
+use crate::interpreter::{self, Value};
 use crate::parallel;
+use crate::visitor::{self, VisitorMut};
+use std::fmt;
 use tokio::runtime::Runtime;
 
 const PARALLELISM_THRESHOLD: usize = 2; 
 
+#[derive(Debug, Clone)]
+pub enum OptimizerWarning {
+    UnreachableCode(SourceLocation),
+    ConstantCondition(bool, SourceLocation),
+}
+
+impl fmt::Display for OptimizerWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizerWarning::UnreachableCode(loc) =>
+                write!(f, "Unreachable code removed at line {}, column {}",
+                    loc.start_line, loc.start_column),
+            OptimizerWarning::ConstantCondition(value, loc) =>
+                write!(f, "Condition is always {}, branch removed at line {}, column {}",
+                    value, loc.start_line, loc.start_column),
+        }
+    }
+}
+
+impl OptimizerWarning {
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            OptimizerWarning::UnreachableCode(loc) |
+            OptimizerWarning::ConstantCondition(_, loc) => *loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            OptimizerWarning::UnreachableCode(..) => "W0001",
+            OptimizerWarning::ConstantCondition(..) => "W0002",
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OptimizerWarning::UnreachableCode(..) => "UnreachableCode",
+            OptimizerWarning::ConstantCondition(..) => "ConstantCondition",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            OptimizerWarning::UnreachableCode(_) => "Unreachable code removed".to_string(),
+            OptimizerWarning::ConstantCondition(value, _) => format!("Condition is always {}, branch removed", value),
+        }
+    }
+}
+
 pub fn optimize_ast(program: Program, runtime: &Runtime) -> Program {
+    optimize_ast_with_warnings(program, runtime).0
+}
+
+pub fn optimize_ast_with_warnings(program: Program, runtime: &Runtime) -> (Program, Vec<OptimizerWarning>) {
     
     let optimized_statements = if program.statements.len() > PARALLELISM_THRESHOLD {
         parallel::process_items_sync(runtime, program.statements, |stmt, rt| {
             optimize_statement(stmt, rt)
         })
             .into_iter()
-            .filter_map(|s| s)
+            .flatten()
             .collect()
     } else {
         optimize_statements(program.statements, runtime)
     };
     
+    let mut optimized = Program::new(optimized_statements, program.location);
     
-    let optimized = Program::new(optimized_statements, program.location);
-  
-    optimized
+    let mut eliminator = DeadCodeEliminator::default();
+    eliminator.visit_program_mut(&mut optimized);
+    eliminator.warnings.sort_by_key(|warning| warning.location().span.start);
+    
+    (optimized, eliminator.warnings)
 }
 
 fn optimize_statements(statements: Vec<Statement>, runtime: &Runtime) -> Vec<Statement> {
     let mut optimized = Vec::new();
     
     for statement in statements {
-        match optimize_statement(statement, runtime) {
-            Some(stmt) => optimized.push(stmt),
-            None => {} 
+        if let Some(stmt) = optimize_statement(statement, runtime) {
+            optimized.push(stmt);
         }
     }
     
@@ -49,7 +107,7 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
             
             
             
-            if optimized_block.statements.len() == 1 {
+            if optimized_block.statements.len() == 1 && !declares_variables(&optimized_block) {
                 match &optimized_block.statements[0] {
                     Statement::Block { .. } => {},
                     _ => return Some(optimized_block.statements.into_iter().next().unwrap())
@@ -60,7 +118,7 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
         },
         Statement::IfStatement { condition, body, else_body, location } => {
             
-            let optimized_condition = optimize_expression(condition, runtime);
+            let optimized_condition = optimize_expression(condition);
             
             
             let optimized_body = optimize_block(body, runtime);
@@ -70,7 +128,7 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
             
             
             if optimized_body.statements.is_empty() && 
-               optimized_else_body.as_ref().map_or(true, |b| b.statements.is_empty()) {
+               optimized_else_body.as_ref().is_none_or(|b| b.statements.is_empty()) {
                 return None;
             }
             
@@ -82,7 +140,7 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
             })
         },
         Statement::ExpressionStatement { expression, location } => {
-            let optimized_expr = optimize_expression(expression, runtime);
+            let optimized_expr = optimize_expression(expression);
             
             Some(Statement::ExpressionStatement { expression: optimized_expr, location })
         },
@@ -99,14 +157,14 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
         },
         Statement::ReturnStatement { value, location } => {
             
-            let optimized_value = optimize_expression(value, runtime);
+            let optimized_value = optimize_expression(value);
             
             
             Some(Statement::ReturnStatement { value: optimized_value, location })
         },
         Statement::LoopStatement { variable, iterable, body, location } => {
             
-            let optimized_iterable = optimize_expression(iterable, runtime);
+            let optimized_iterable = optimize_expression(iterable);
             
             
             let optimized_body = optimize_block(body, runtime);
@@ -126,10 +184,11 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
         
         Statement::EndStatement { location } => Some(Statement::EndStatement { location }),
         Statement::ContinueStatement { location } => Some(Statement::ContinueStatement { location }),
+        Statement::ErrorStatement { message, location } => Some(Statement::ErrorStatement { message, location }),
         
         
         Statement::VariableDeclaration { name, value, location } => {
-            let optimized_value = optimize_expression(value, runtime);
+            let optimized_value = optimize_expression(value);
             Some(Statement::VariableDeclaration { name, value: optimized_value, location })
         }
     }
@@ -138,9 +197,9 @@ fn optimize_statement(statement: Statement, runtime: &Runtime) -> Option<Stateme
 fn optimize_block(block: Block, runtime: &Runtime) -> Block {
     
     let statements = if block.statements.len() > PARALLELISM_THRESHOLD {
-        parallel::process_items_sync(runtime, block.statements, |stmt, rt| optimize_statement(stmt, rt))
+        parallel::process_items_sync(runtime, block.statements, optimize_statement)
             .into_iter()
-            .filter_map(|s| s)
+            .flatten()
             .collect()
     } else {
         optimize_statements(block.statements, runtime)
@@ -151,7 +210,7 @@ fn optimize_block(block: Block, runtime: &Runtime) -> Block {
     for stmt in statements {
         match stmt {
             
-            Statement::Block { block: inner_block, .. } => {
+            Statement::Block { block: inner_block, .. } if !declares_variables(&inner_block) => {
                 flattened.extend(inner_block.statements);
             },
             _ => flattened.push(stmt)
@@ -161,138 +220,223 @@ fn optimize_block(block: Block, runtime: &Runtime) -> Block {
     Block::new(flattened, block.location)
 }
 
+// Every block opens a scope, so a block that declares variables must stay a block.
+fn declares_variables(block: &Block) -> bool {
+    block.statements.iter().any(|statement| matches!(statement, Statement::VariableDeclaration { .. }))
+}
 
-fn optimize_expression(expr: Expression, runtime: &Runtime) -> Expression {
-    match expr {
-        Expression::BinaryExpression { left, operator, right, location } => {
-            
-            let optimized_left = optimize_expression(*left, runtime);
-            let optimized_right = optimize_expression(*right, runtime);
-            let result = Expression::BinaryExpression {
-                left: Box::new(optimized_left),
-                operator,
-                right: Box::new(optimized_right),
-                location,
-            };
-            result
-        },
-        Expression::AssignmentExpression { target, value, location } => {
-            let optimized_value = Box::new(optimize_expression(*value, runtime));
-            let result = Expression::AssignmentExpression { target, value: optimized_value, location };
-            result
-        },
-        Expression::MemberAssignmentExpression { object, property, property_expr, computed, value, location } => {
-            
-            let optimized_object = Box::new(optimize_expression(*object, runtime));
-            
-            
-            let optimized_prop_expr = match property_expr {
-                Some(expr) => Some(Box::new(optimize_expression(*expr, runtime))),
-                None => None,
-            };
-            
-            let optimized_value = Box::new(optimize_expression(*value, runtime));
-            let result = Expression::MemberAssignmentExpression {
-                object: optimized_object,
-                property,
-                property_expr: optimized_prop_expr,
-                computed,
-                value: optimized_value,
-                location,
-            };
-            result
-        },
-        Expression::CallExpression { callee, arguments, location } => {
-            
-            let optimized_args = if arguments.len() > PARALLELISM_THRESHOLD {
-                parallel::process_items_sync(runtime, arguments, |arg, rt| optimize_expression(arg, rt))
-            } else {
-                arguments.into_iter().map(|arg| optimize_expression(arg, runtime)).collect()
-            };
-            let result = Expression::CallExpression { callee, arguments: optimized_args, location };
-            result
-        },
-        Expression::ArrayExpression { elements, location } => {
-            
-            let optimized_elements = if elements.len() > PARALLELISM_THRESHOLD {
-                parallel::process_items_sync(runtime, elements, |elem, rt| optimize_expression(elem, rt))
-            } else {
-                elements.into_iter().map(|elem| optimize_expression(elem, runtime)).collect()
-            };
-            let result = Expression::ArrayExpression { elements: optimized_elements, location };
-            result
-        },
-        Expression::ObjectExpression { properties, location } => {
-            
-            let optimized_properties = if properties.len() > PARALLELISM_THRESHOLD {
-                parallel::process_items_sync(
-                    runtime,
-                    properties,
-                    |prop, rt| Property::new(prop.key, optimize_expression(prop.value, rt), prop.location)
-                )
-            } else {
-                properties.into_iter()
-                    .map(|prop| Property::new(prop.key, optimize_expression(prop.value, runtime), prop.location))
-                    .collect()
-            };
-            let result = Expression::ObjectExpression { properties: optimized_properties, location };
-            result
+
+struct ExpressionOptimizer;
+
+impl VisitorMut for ExpressionOptimizer {
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        visitor::walk_expression_mut(self, expression);
+
+        if let Some(folded) = fold_constant(expression) {
+            *expression = folded;
+        }
+    }
+}
+
+fn fold_constant(expression: &Expression) -> Option<Expression> {
+    let (value, location) = match expression {
+        Expression::BinaryExpression { left, operator, right, .. } => {
+            let left_value = literal_value(left)?;
+            let right_value = literal_value(right)?;
+            let location = left.location().merge(&right.location());
+            (interpreter::apply_binary_operator(operator, left_value, right_value, location).ok()?, location)
         },
-        Expression::MemberExpression { object, property, property_expr, computed, location } => {
-            
-            let optimized_object = Box::new(optimize_expression(*object, runtime));
-            
-            
-            let optimized_prop_expr = match property_expr {
-                Some(expr) => Some(Box::new(optimize_expression(*expr, runtime))),
-                None => None,
-            };
-            let result = Expression::MemberExpression {
-                object: optimized_object,
-                property,
-                property_expr: optimized_prop_expr,
-                computed,
-                location,
+        Expression::UnaryExpression { operator, operand, location } => {
+            let value = match (operator, literal_value(operand)?) {
+                (Operator::Not, value) => Value::Boolean(!value.is_truthy()),
+                (Operator::Minus, Value::Number(value)) => Value::Number(-value),
+                _ => return None,
             };
-            result
-        },
-        Expression::KeysOfExpression { object, location } => {
-            let optimized_object = Box::new(optimize_expression(*object, runtime));
-            let result = Expression::KeysOfExpression { object: optimized_object, location };
-            result
+            (value, *location)
         },
-        Expression::MemberCallExpression { object, property, property_expr, computed, arguments, location } => {
-            
-            let optimized_object = Box::new(optimize_expression(*object, runtime));
-            
-            
-            let optimized_prop_expr = match property_expr {
-                Some(expr) => Some(Box::new(optimize_expression(*expr, runtime))),
-                None => None,
-            };
-            
-            
-            let optimized_args = if arguments.len() > PARALLELISM_THRESHOLD {
-                parallel::process_items_sync(runtime, arguments, |arg, rt| optimize_expression(arg, rt))
-            } else {
-                arguments.into_iter().map(|arg| optimize_expression(arg, runtime)).collect()
-            };
-            
-            Expression::MemberCallExpression {
-                object: optimized_object,
-                property,
-                property_expr: optimized_prop_expr,
-                computed,
-                arguments: optimized_args,
-                location,
+        _ => return None,
+    };
+
+    match value {
+        Value::Number(value) if value.is_finite() => Some(Expression::NumberLiteral { value, location }),
+        Value::String(value) => Some(Expression::StringLiteral { value, location }),
+        Value::Boolean(value) => Some(Expression::BooleanLiteral { value, location }),
+        Value::Null => Some(Expression::NullLiteral { location }),
+        _ => None,
+    }
+}
+
+fn literal_value(expression: &Expression) -> Option<Value> {
+    match expression {
+        Expression::NumberLiteral { value, .. } => Some(Value::Number(*value)),
+        Expression::StringLiteral { value, .. } => Some(Value::String(value.clone())),
+        Expression::BooleanLiteral { value, .. } => Some(Value::Boolean(*value)),
+        Expression::NullLiteral { .. } => Some(Value::Null),
+        _ => None,
+    }
+}
+
+fn optimize_expression(mut expr: Expression) -> Expression {
+    ExpressionOptimizer.visit_expression_mut(&mut expr);
+    expr
+}
+
+#[derive(Default)]
+struct DeadCodeEliminator {
+    warnings: Vec<OptimizerWarning>,
+}
+
+impl DeadCodeEliminator {
+    fn eliminate(&mut self, statements: &mut Vec<Statement>) {
+        let mut reachable: Vec<Statement> = statements.drain(..)
+            .filter_map(|statement| self.resolve_constant_branch(statement))
+            .collect();
+
+        if let Some(position) = reachable.iter().position(is_terminator) {
+            let unreachable = reachable.split_off(position + 1);
+            if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+                self.warnings.push(OptimizerWarning::UnreachableCode(first.location().merge(&last.location())));
             }
-        },
-        
-        Expression::StringLiteral { .. } |
-        Expression::NumberLiteral { .. } |
-        Expression::Identifier { .. } |
-        Expression::CallbackReference { .. } => {
-            expr
-        },
+        }
+
+        *statements = reachable;
+    }
+
+    fn resolve_constant_branch(&mut self, statement: Statement) -> Option<Statement> {
+        let Statement::IfStatement { condition, body, else_body, location } = statement else {
+            return Some(statement);
+        };
+
+        let Some(taken) = constant_truthiness(&condition) else {
+            return Some(Statement::IfStatement { condition, body, else_body, location });
+        };
+
+        if taken {
+            if let Some(else_body) = &else_body {
+                self.warnings.push(OptimizerWarning::ConstantCondition(true, else_body.location));
+            }
+            Some(Statement::Block { block: body, location })
+        } else {
+            self.warnings.push(OptimizerWarning::ConstantCondition(false, body.location));
+            else_body.map(|block| Statement::Block { block, location })
+        }
+    }
+}
+
+impl VisitorMut for DeadCodeEliminator {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        self.eliminate(&mut program.statements);
+        visitor::walk_program_mut(self, program);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        self.eliminate(&mut block.statements);
+        visitor::walk_block_mut(self, block);
+    }
+}
+
+fn is_terminator(statement: &Statement) -> bool {
+    matches!(statement,
+        Statement::ReturnStatement { .. } |
+        Statement::EndStatement { .. } |
+        Statement::ContinueStatement { .. })
+}
+
+fn constant_truthiness(expression: &Expression) -> Option<bool> {
+    match expression {
+        Expression::BooleanLiteral { value, .. } => Some(*value),
+        Expression::NullLiteral { .. } => Some(false),
+        _ => literal_value(expression).map(|value| value.is_truthy()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlags;
+
+    fn returned(code: &str) -> Expression {
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses");
+        match program.statements.into_iter().last() {
+            Some(Statement::ReturnStatement { value, .. }) => value,
+            other => panic!("expected a return statement, got {:?}", other),
+        }
+    }
+
+    fn warnings(code: &str) -> Vec<OptimizerWarning> {
+        crate::process_code_with_warnings(code, FeatureFlags::all_enabled(), Default::default())
+            .expect("test script parses")
+            .1
+    }
+
+    fn run(code: &str) -> Value {
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses");
+        let mut host = |name: &str, _: Vec<Value>| Err(format!("Unknown function: {}", name));
+        interpreter::execute(&program, &mut host).expect("test script runs")
+    }
+
+    #[test]
+    fn removes_code_after_terminators() {
+        let code = "cb f() { res 1; g(); h(); }";
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).unwrap();
+        let Some(Statement::CallbackDeclaration { body, .. }) = program.statements.first() else {
+            panic!("expected a callback declaration");
+        };
+        assert_eq!(body.statements.len(), 1);
+
+        let warnings = warnings(code);
+        assert!(matches!(warnings.as_slice(), [OptimizerWarning::UnreachableCode(location)] if location.start_column == 17));
+    }
+
+    #[test]
+    fn warns_about_unreachable_code_in_nested_blocks() {
+        let warnings = warnings("loop i in a { if i { end; f(); } else { continue; g(); } } cb h() { loop j in b { res j; k(); } }");
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings.iter().all(|warning| matches!(warning, OptimizerWarning::UnreachableCode(_))));
+    }
+
+    #[test]
+    fn warns_about_constant_conditions() {
+        assert!(matches!(warnings("if true { f(); } else { g(); } if 1 > 2 { h(); }").as_slice(), [
+            OptimizerWarning::ConstantCondition(true, _),
+            OptimizerWarning::ConstantCondition(false, _),
+        ]));
+        assert!(warnings("if a { f(); }").is_empty());
+    }
+
+    #[test]
+    fn constant_branches_keep_their_scope() {
+        assert_eq!(run("vl x = 1; if true { vl x = 2; } res x;"), Value::Number(1.0));
+        assert_eq!(run("vl x = 1; if false { } else { vl x = 2; } res x;"), Value::Number(1.0));
+        assert_eq!(run("vl x = 1; if true { x = 2; } res x;"), Value::Number(2.0));
+    }
+
+    #[test]
+    fn folds_arithmetic_and_comparisons() {
+        assert!(matches!(returned("res 60 * 60 * 24;"), Expression::NumberLiteral { value, .. } if value == 86400.0));
+        assert!(matches!(returned("res 1 < 2 == true;"), Expression::BooleanLiteral { value: true, .. }));
+        assert!(matches!(returned("res null == null;"), Expression::BooleanLiteral { value: true, .. }));
+    }
+
+    #[test]
+    fn folds_unary_operators() {
+        assert!(matches!(returned("res !false;"), Expression::BooleanLiteral { value: true, .. }));
+        assert!(matches!(returned("res !(1 < 2);"), Expression::BooleanLiteral { value: false, .. }));
+        assert!(matches!(returned("res -(2 * 3);"), Expression::NumberLiteral { value, .. } if value == -6.0));
+        assert!(matches!(returned("res -\"a\";"), Expression::UnaryExpression { .. }));
+    }
+
+    #[test]
+    fn folds_string_concatenation() {
+        assert!(matches!(returned("res \"a\" + 1 + true;"), Expression::StringLiteral { value, .. } if value == "a1true"));
+        assert!(matches!(returned("res \"a\" + \"b\";"), Expression::StringLiteral { value, .. } if value == "ab"));
+    }
+
+    #[test]
+    fn leaves_runtime_errors_and_non_finite_results() {
+        assert!(matches!(returned("res 1 / 0;"), Expression::BinaryExpression { .. }));
+        assert!(matches!(returned("res \"x\" - 1;"), Expression::BinaryExpression { .. }));
+        let overflow = format!("res 1{} * 10;", "0".repeat(308));
+        assert!(matches!(returned(&overflow), Expression::BinaryExpression { .. }));
     }
 }
@@ -1,4 +1,4 @@
-use crate::ast_structs::{Block, Expression, Operator, Program, Property, Statement, SourceLocation};
+use crate::ast_structs::{Block, ColumnEncoding, Expression, LineIndex, Operator, Program, Property, Statement, SourceLocation};
 use crate::feature_flags::FeatureFlags;
 use crate::lexer::{Token, TokenWithSpan};
 use std::fmt;
@@ -10,42 +10,114 @@ pub struct Parser<'a> {
     current_token: Option<&'a TokenWithSpan>,
     flags: FeatureFlags,
     source_code: &'a str,
+    line_index: LineIndex<'a>,
+    depth: usize,
+    recover: bool,
+    errors: Vec<ParseError>,
 }
 
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedToken(String, SourceLocation),
-    ExpectedToken(String, SourceLocation),
+    ExpectedToken(Vec<String>, Option<String>, SourceLocation),
     EndOfInput(SourceLocation),
     FeatureDisabled(String, SourceLocation),
+    LexError(String, String, SourceLocation),
+    InvalidAssignmentTarget(SourceLocation),
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let loc = self.location();
+        write!(f, "{} at line {}, column {}", self.description(), loc.start_line, loc.start_column)
+    }
+}
+
+impl ParseError {
+    pub fn description(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken(token, _) => 
+                format!("Unexpected token: '{}'", token),
+            ParseError::ExpectedToken(expected, Some(found), _) => 
+                format!("Expected token: {}, found '{}'", expected.join(" or "), found),
+            ParseError::ExpectedToken(expected, None, _) => 
+                format!("Expected token: {}", expected.join(" or ")),
+            ParseError::EndOfInput(_) => 
+                "Unexpected end of input".to_string(),
+            ParseError::FeatureDisabled(feature, _) => 
+                format!("Feature disabled: {} is not allowed with current settings", feature),
+            ParseError::LexError(msg, _, _) => 
+                format!("Lexical error: {}", msg),
+            ParseError::InvalidAssignmentTarget(_) => 
+                "Invalid assignment target".to_string(),
+        }
+    }
+
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            ParseError::UnexpectedToken(_, loc) |
+            ParseError::ExpectedToken(_, _, loc) |
+            ParseError::EndOfInput(loc) |
+            ParseError::FeatureDisabled(_, loc) |
+            ParseError::LexError(_, _, loc) |
+            ParseError::InvalidAssignmentTarget(loc) => *loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedToken(..) => "E0001",
+            ParseError::ExpectedToken(..) => "E0002",
+            ParseError::EndOfInput(..) => "E0003",
+            ParseError::FeatureDisabled(..) => "E0004",
+            ParseError::LexError(..) => "E0005",
+            ParseError::InvalidAssignmentTarget(..) => "E0006",
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedToken(..) => "UnexpectedToken",
+            ParseError::ExpectedToken(..) => "ExpectedToken",
+            ParseError::EndOfInput(..) => "EndOfInput",
+            ParseError::FeatureDisabled(..) => "FeatureDisabled",
+            ParseError::LexError(..) => "LexError",
+            ParseError::InvalidAssignmentTarget(..) => "InvalidAssignmentTarget",
+        }
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            ParseError::UnexpectedToken(token, _) |
+            ParseError::LexError(_, token, _) => Some(token),
+            ParseError::ExpectedToken(_, found, _) => found.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn expected(&self) -> &[String] {
         match self {
-            ParseError::UnexpectedToken(msg, loc) => 
-                write!(f, "Unexpected token: {} at line {}, column {}", 
-                    msg, loc.start_line, loc.start_column),
-            ParseError::ExpectedToken(msg, loc) => 
-                write!(f, "Expected token: {} at line {}, column {}", 
-                    msg, loc.start_line, loc.start_column),
-            ParseError::EndOfInput(loc) => 
-                write!(f, "Unexpected end of input at line {}, column {}", 
-                    loc.start_line, loc.start_column),
-            ParseError::FeatureDisabled(feature, loc) => 
-                write!(f, "Feature disabled: {} is not allowed with current settings at line {}, column {}", 
-                    feature, loc.start_line, loc.start_column),
+            ParseError::ExpectedToken(expected, _, _) => expected,
+            _ => &[],
         }
     }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a [TokenWithSpan], flags: FeatureFlags, source_code: &'a str) -> Self {
+        Self::with_column_encoding(tokens, flags, source_code, ColumnEncoding::default())
+    }
+
+    pub fn with_column_encoding(tokens: &'a [TokenWithSpan], flags: FeatureFlags, source_code: &'a str, encoding: ColumnEncoding) -> Self {
         let mut parser = Parser {
             tokens: tokens.iter().peekable(),
             current_token: None,
             flags,
             source_code,
+            line_index: LineIndex::with_encoding(source_code, encoding),
+            depth: 0,
+            recover: false,
+            errors: Vec::new(),
         };
         parser.advance();
         parser
@@ -53,15 +125,22 @@ impl<'a> Parser<'a> {
 
     fn current_location(&self) -> SourceLocation {
         match self.current_token {
-            Some(token) => token.get_location(self.source_code),
+            Some(token) => token.get_location(&self.line_index),
             None => {
                 let end_pos = self.source_code.len();
-                SourceLocation::from_spans(self.source_code, end_pos, end_pos)
+                self.line_index.location(end_pos, end_pos)
             }
         }
     }
 
     fn advance(&mut self) {
+        if let Some(token_with_span) = self.current_token {
+            match token_with_span.token {
+                Token::OpenBrace => self.depth += 1,
+                Token::CloseBrace => self.depth = self.depth.saturating_sub(1),
+                _ => {}
+            }
+        }
         self.current_token = self.tokens.next();
     }
 
@@ -76,41 +155,120 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Ok(())
             }
-            Some(token_with_span) => Err(ParseError::UnexpectedToken(format!(
-                "Expected {:?}, got {:?}",
-                expected, token_with_span.token
-            ), location)),
+            Some(token_with_span) => Err(ParseError::ExpectedToken(
+                vec![expected.to_string()],
+                Some(self.source_code[token_with_span.span.clone()].to_string()),
+                location
+            )),
             None => Err(ParseError::EndOfInput(location)),
         }
     }
 
+    fn expected_error(&self, expected: &[&str]) -> ParseError {
+        ParseError::ExpectedToken(
+            expected.iter().map(|e| e.to_string()).collect(),
+            self.current_token.map(|t| self.source_code[t.span.clone()].to_string()),
+            self.current_location()
+        )
+    }
+
     pub fn parse_program(&mut self) -> Result<Program, ParseError> {
         let start_location = self.current_location();
         
         let mut statements = Vec::new();
 
         while self.current_token.is_some() {
-            let stmt = self.parse_statement()?;
+            let stmt = self.parse_statement_or_recover()?;
             statements.push(stmt);
         }
 
         let end_location = if let Some(last_token) = self.tokens.clone().last() {
-            last_token.get_location(self.source_code)
+            last_token.get_location(&self.line_index)
         } else {
             let end_pos = self.source_code.len();
-            SourceLocation::from_spans(self.source_code, end_pos, end_pos)
+            self.line_index.location(end_pos, end_pos)
         };
         
-        let program_location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let program_location = start_location.merge(&end_location);
 
         Ok(Program::new(statements, program_location))
     }
 
+    pub fn parse_program_with_diagnostics(&mut self) -> (Program, Vec<ParseError>) {
+        self.recover = true;
+        
+        let program = match self.parse_program() {
+            Ok(program) => program,
+            Err(error) => {
+                let location = error.location();
+                self.errors.push(error);
+                Program::new(Vec::new(), location)
+            }
+        };
+        
+        (program, std::mem::take(&mut self.errors))
+    }
+
+    fn parse_statement_or_recover(&mut self) -> Result<Statement, ParseError> {
+        let start_location = self.current_location();
+        let depth = self.depth;
+        
+        match self.parse_statement() {
+            Ok(stmt) => Ok(stmt),
+            Err(error) if self.recover => {
+                let message = error.to_string();
+                let end_location = self.synchronize(depth).unwrap_or_else(|| error.location());
+                if self.errors.last().map(|last| last.to_string()) != Some(message.clone()) {
+                    self.errors.push(error);
+                }
+                
+                let location = start_location.merge(&end_location);
+                
+                Ok(Statement::ErrorStatement { message, location })
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    fn synchronize(&mut self, depth: usize) -> Option<SourceLocation> {
+        let mut last_location = None;
+        let unclosed_depth = self.depth;
+        
+        while let Some(token_with_span) = self.current_token {
+            match token_with_span.token {
+                Token::Vl | Token::Cb | Token::If | Token::Loop if self.depth > depth && self.depth <= unclosed_depth => {
+                    self.depth = depth;
+                    break;
+                },
+                Token::Semicolon if self.depth == depth => {
+                    last_location = Some(self.current_location());
+                    self.advance();
+                    break;
+                },
+                Token::CloseBrace if self.depth == depth => {
+                    if depth == 0 {
+                        last_location = Some(self.current_location());
+                        self.advance();
+                    }
+                    break;
+                },
+                Token::CloseBrace if self.depth == depth + 1 => {
+                    last_location = Some(self.current_location());
+                    self.advance();
+                    if self.current_token.is_some_and(|token_with_span| starts_statement(&token_with_span.token)) {
+                        break;
+                    }
+                },
+                _ => {
+                    last_location = Some(self.current_location());
+                    self.advance();
+                }
+            }
+        }
+        
+        last_location
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, ParseError> {
         let start_location = self.current_location();
         
@@ -171,12 +329,7 @@ impl<'a> Parser<'a> {
                     let end_location = self.current_location();
                     self.expect(Token::Semicolon)?;
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        end_location.end_line,
-                        end_location.end_column
-                    );
+                    let location = start_location.merge(&end_location);
                     
                     Ok(Statement::ExpressionStatement { 
                         expression: expr,
@@ -196,7 +349,7 @@ impl<'a> Parser<'a> {
         let name = match &self.current_token {
             Some(token_with_span) => match &token_with_span.token {
                 Token::Identifier(name) => name.clone(),
-                _ => return Err(ParseError::ExpectedToken("identifier".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["identifier"])),
             },
             None => return Err(ParseError::EndOfInput(self.current_location())),
         };
@@ -211,12 +364,7 @@ impl<'a> Parser<'a> {
 
         let end_location = semicolon_location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::VariableDeclaration { name, value, location })
     }
@@ -230,7 +378,16 @@ impl<'a> Parser<'a> {
         let else_body = if let Some(token_with_span) = self.current_token {
             if token_with_span.token == Token::Else {
                 self.advance();
-                Some(self.parse_block()?)
+                
+                match self.current_token {
+                    Some(token_with_span) if token_with_span.token == Token::If => {
+                        let else_if_location = self.current_location();
+                        let else_if = self.parse_if_statement(else_if_location)?;
+                        let location = else_if.location();
+                        Some(Block::new(vec![else_if], location))
+                    },
+                    _ => Some(self.parse_block()?),
+                }
             } else {
                 None
             }
@@ -239,16 +396,11 @@ impl<'a> Parser<'a> {
         };
 
         let end_location = else_body.as_ref().map_or_else(
-            || body.location.clone(),
-            |else_block| else_block.location.clone()
+            || body.location,
+            |else_block| else_block.location
         );
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::IfStatement {
             condition, 
@@ -261,14 +413,9 @@ impl<'a> Parser<'a> {
     fn parse_block_statement(&mut self, start_location: SourceLocation) -> Result<Statement, ParseError> {
         let block = self.parse_block()?;
         
-        let end_location = block.location.clone();
+        let end_location = block.location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::Block { block, location })
     }
@@ -283,18 +430,13 @@ impl<'a> Parser<'a> {
             if token_with_span.token == Token::CloseBrace {
                 break;
             }
-            statements.push(self.parse_statement()?);
+            statements.push(self.parse_statement_or_recover()?);
         }
 
         let end_location = self.current_location();
         self.expect(Token::CloseBrace)?;
 
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Block::new(statements, location))
     }
@@ -306,64 +448,106 @@ impl<'a> Parser<'a> {
 
     fn parse_assignment(&mut self) -> Result<Expression, ParseError> {
         let start_location = self.current_location();
-        let expr = self.parse_equality()?;
+        let expr = self.parse_logical_or()?;
         
-        if let Some(token_with_span) = self.current_token {
-            if token_with_span.token == Token::Equal {
-                if !self.flags.allow_assignments {
-                    return Err(ParseError::FeatureDisabled("Assignments".to_string(), start_location));
-                }
-                
-                self.advance();
-                let value = self.parse_equality()?;
-                let end_location = match &value {
-                    Expression::StringLiteral { location, .. } |
-                    Expression::NumberLiteral { location, .. } |
-                    Expression::Identifier { location, .. } |
-                    Expression::BinaryExpression { location, .. } |
-                    Expression::AssignmentExpression { location, .. } |
-                    Expression::MemberAssignmentExpression { location, .. } |
-                    Expression::CallExpression { location, .. } |
-                    Expression::MemberCallExpression { location, .. } |  
-                    Expression::CallbackReference { location, .. } |
-                    Expression::ArrayExpression { location, .. } |
-                    Expression::ObjectExpression { location, .. } |
-                    Expression::MemberExpression { location, .. } |
-                    Expression::KeysOfExpression { location, .. } => location.clone(),
-                };
-                
-                let location = SourceLocation::new(
-                    start_location.start_line,
-                    start_location.start_column,
-                    end_location.end_line,
-                    end_location.end_column
-                );
-                
-                let new_expr = match expr {
-                    Expression::Identifier { name, .. } => Expression::AssignmentExpression {
-                        target: name,
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::Equal {
+            if !self.flags.allow_assignments {
+                return Err(ParseError::FeatureDisabled("Assignments".to_string(), start_location));
+            }
+            
+            self.advance();
+            let value = self.parse_logical_or()?;
+            let end_location = value.location();
+            
+            let location = start_location.merge(&end_location);
+            
+            let new_expr = match expr {
+                Expression::Identifier { name, .. } => Expression::AssignmentExpression {
+                    target: name,
+                    value: Box::new(value),
+                    location,
+                },
+                Expression::MemberExpression { object, property, property_expr, computed, .. } => {
+                    if !self.flags.allow_object_navigation {
+                        return Err(ParseError::FeatureDisabled("Object property assignment".to_string(), start_location));
+                    }
+                    
+                    Expression::MemberAssignmentExpression {
+                        object,
+                        property,
+                        property_expr,
+                        computed,
                         value: Box::new(value),
                         location,
-                    },
-                    Expression::MemberExpression { object, property, property_expr, computed, .. } => {
-                        if !self.flags.allow_object_navigation {
-                            return Err(ParseError::FeatureDisabled("Object property assignment".to_string(), start_location));
-                        }
-                        
-                        Expression::MemberAssignmentExpression {
-                            object,
-                            property,
-                            property_expr,
-                            computed,
-                            value: Box::new(value),
-                            location,
-                        }
-                    },
-                    _ => return Err(ParseError::UnexpectedToken("Invalid assignment target".to_string(), start_location)),
-                };
-                return Ok(new_expr);
+                    }
+                },
+                _ => return Err(ParseError::InvalidAssignmentTarget(start_location)),
+            };
+            return Ok(new_expr);
+        }
+        Ok(expr)
+    }
+
+    fn parse_logical_or(&mut self) -> Result<Expression, ParseError> {
+        let start_location = self.current_location();
+        let mut expr = self.parse_logical_and()?;
+        
+        while let Some(token_with_span) = self.current_token {
+            match &token_with_span.token {
+                Token::Or => {
+                    if !self.flags.allow_logical_operators {
+                        return Err(ParseError::FeatureDisabled("Logical operators".to_string(), self.current_location()));
+                    }
+                    
+                    self.advance();
+                    let right = self.parse_logical_and()?;
+                    let right_loc = right.location();
+                    
+                    let location = start_location.merge(&right_loc);
+                    
+                    expr = Expression::LogicalExpression {
+                        left: Box::new(expr),
+                        operator: Operator::Or,
+                        right: Box::new(right),
+                        location,
+                    };
+                }
+                _ => break,
+            }
+        }
+        
+        Ok(expr)
+    }
+
+    fn parse_logical_and(&mut self) -> Result<Expression, ParseError> {
+        let start_location = self.current_location();
+        let mut expr = self.parse_equality()?;
+        
+        while let Some(token_with_span) = self.current_token {
+            match &token_with_span.token {
+                Token::And => {
+                    if !self.flags.allow_logical_operators {
+                        return Err(ParseError::FeatureDisabled("Logical operators".to_string(), self.current_location()));
+                    }
+                    
+                    self.advance();
+                    let right = self.parse_equality()?;
+                    let right_loc = right.location();
+                    
+                    let location = start_location.merge(&right_loc);
+                    
+                    expr = Expression::LogicalExpression {
+                        left: Box::new(expr),
+                        operator: Operator::And,
+                        right: Box::new(right),
+                        location,
+                    };
+                }
+                _ => break,
             }
         }
+        
         Ok(expr)
     }
 
@@ -374,30 +558,15 @@ impl<'a> Parser<'a> {
         while let Some(token_with_span) = self.current_token {
             expr = match token_with_span.token {
                 Token::Greater => {
+                    if !self.flags.allow_comparison_operators {
+                        return Err(ParseError::FeatureDisabled("Comparison operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_additive()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -407,30 +576,15 @@ impl<'a> Parser<'a> {
                     }
                 },
                 Token::Less => {
+                    if !self.flags.allow_comparison_operators {
+                        return Err(ParseError::FeatureDisabled("Comparison operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_additive()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -440,30 +594,15 @@ impl<'a> Parser<'a> {
                     }
                 },
                 Token::GreaterEqual => {
+                    if !self.flags.allow_comparison_operators {
+                        return Err(ParseError::FeatureDisabled("Comparison operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_additive()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -473,30 +612,15 @@ impl<'a> Parser<'a> {
                     }
                 },
                 Token::LessEqual => {
+                    if !self.flags.allow_comparison_operators {
+                        return Err(ParseError::FeatureDisabled("Comparison operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_additive()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -518,30 +642,15 @@ impl<'a> Parser<'a> {
         while let Some(token_with_span) = self.current_token {
             match &token_with_span.token {
                 Token::EqualEqual => {
+                    if !self.flags.allow_equality {
+                        return Err(ParseError::FeatureDisabled("Equality operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_comparison()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     expr = Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -550,6 +659,24 @@ impl<'a> Parser<'a> {
                         location,
                     };
                 }
+                Token::NotEqual => {
+                    if !self.flags.allow_equality {
+                        return Err(ParseError::FeatureDisabled("Equality operators".to_string(), self.current_location()));
+                    }
+                    
+                    self.advance();
+                    let right = self.parse_comparison()?;
+                    let right_loc = right.location();
+                    
+                    let location = start_location.merge(&right_loc);
+                    
+                    expr = Expression::BinaryExpression {
+                        left: Box::new(expr),
+                        operator: Operator::NotEqual,
+                        right: Box::new(right),
+                        location,
+                    };
+                }
                 _ => break,
             }
         }
@@ -564,30 +691,15 @@ impl<'a> Parser<'a> {
         while let Some(token_with_span) = self.current_token {
             match &token_with_span.token {
                 Token::Plus => {
+                    if !self.flags.allow_arithmetic_operators {
+                        return Err(ParseError::FeatureDisabled("Arithmetic operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
                     let right = self.parse_multiplicative()?;
-                    let right_loc = match &right {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_loc = right.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     expr = Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -596,6 +708,24 @@ impl<'a> Parser<'a> {
                         location,
                     };
                 }
+                Token::Minus => {
+                    if !self.flags.allow_arithmetic_operators {
+                        return Err(ParseError::FeatureDisabled("Arithmetic operators".to_string(), self.current_location()));
+                    }
+                    
+                    self.advance();
+                    let right = self.parse_multiplicative()?;
+                    let right_loc = right.location();
+                    
+                    let location = start_location.merge(&right_loc);
+                    
+                    expr = Expression::BinaryExpression {
+                        left: Box::new(expr),
+                        operator: Operator::Minus,
+                        right: Box::new(right),
+                        location,
+                    };
+                }
                 _ => break,
             }
         }
@@ -605,38 +735,20 @@ impl<'a> Parser<'a> {
     
     fn parse_multiplicative(&mut self) -> Result<Expression, ParseError> {
         let start_location = self.current_location();
-        let mut expr = self.parse_primary()?;
-        
-        expr = self.parse_member_access(expr)?;
+        let mut expr = self.parse_unary()?;
 
         while let Some(token_with_span) = self.current_token {
             match &token_with_span.token {
                 Token::Multiply => {
+                    if !self.flags.allow_arithmetic_operators {
+                        return Err(ParseError::FeatureDisabled("Arithmetic operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
-                    let right = self.parse_primary()?;
-                    let right_with_member = self.parse_member_access(right)?;
-                    let right_loc = match &right_with_member {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_with_member = self.parse_unary()?;
+                    let right_loc = right_with_member.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     expr = Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -646,31 +758,15 @@ impl<'a> Parser<'a> {
                     };
                 }
                 Token::Divide => {
+                    if !self.flags.allow_arithmetic_operators {
+                        return Err(ParseError::FeatureDisabled("Arithmetic operators".to_string(), self.current_location()));
+                    }
+                    
                     self.advance();
-                    let right = self.parse_primary()?;
-                    let right_with_member = self.parse_member_access(right)?;
-                    let right_loc = match &right_with_member {
-                        Expression::StringLiteral { location, .. } |
-                        Expression::NumberLiteral { location, .. } |
-                        Expression::Identifier { location, .. } |
-                        Expression::BinaryExpression { location, .. } |
-                        Expression::AssignmentExpression { location, .. } |
-                        Expression::MemberAssignmentExpression { location, .. } |
-                        Expression::CallExpression { location, .. } |
-                        Expression::MemberCallExpression { location, .. } |  
-                        Expression::CallbackReference { location, .. } |
-                        Expression::ArrayExpression { location, .. } |
-                        Expression::ObjectExpression { location, .. } |
-                        Expression::MemberExpression { location, .. } |
-                        Expression::KeysOfExpression { location, .. } => location.clone(),
-                    };
+                    let right_with_member = self.parse_unary()?;
+                    let right_loc = right_with_member.location();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        right_loc.end_line,
-                        right_loc.end_column
-                    );
+                    let location = start_location.merge(&right_loc);
                     
                     expr = Expression::BinaryExpression {
                         left: Box::new(expr),
@@ -686,6 +782,60 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    fn parse_unary(&mut self) -> Result<Expression, ParseError> {
+        let start_location = self.current_location();
+        
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::Not {
+            if !self.flags.allow_logical_operators {
+                return Err(ParseError::FeatureDisabled("Logical operators".to_string(), start_location));
+            }
+            
+            self.advance();
+            let operand = self.parse_unary()?;
+            let operand_loc = operand.location();
+            
+            let location = start_location.merge(&operand_loc);
+            
+            return Ok(Expression::UnaryExpression {
+                operator: Operator::Not,
+                operand: Box::new(operand),
+                location,
+            });
+        }
+        
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::Minus {
+            self.advance();
+            let operand = self.parse_unary()?;
+            
+            return Ok(match operand {
+                Expression::NumberLiteral { value, location } => Expression::NumberLiteral {
+                    value: -value,
+                    location: start_location.merge(&location),
+                },
+                operand => {
+                    if !self.flags.allow_arithmetic_operators {
+                        return Err(ParseError::FeatureDisabled("Arithmetic operators".to_string(), start_location));
+                    }
+                    
+                    let operand_loc = operand.location();
+                    
+                    let location = start_location.merge(&operand_loc);
+                    
+                    Expression::UnaryExpression {
+                        operator: Operator::Minus,
+                        operand: Box::new(operand),
+                        location,
+                    }
+                },
+            });
+        }
+        
+        let expr = self.parse_primary()?;
+        self.parse_member_access(expr)
+    }
+
     fn parse_primary(&mut self) -> Result<Expression, ParseError> {
         let start_location = self.current_location();
         
@@ -696,28 +846,9 @@ impl<'a> Parser<'a> {
                         self.advance();
                         
                         let object_expr = self.parse_primary()?;
-                        let object_loc = match &object_expr {
-                            Expression::StringLiteral { location, .. } |
-                            Expression::NumberLiteral { location, .. } |
-                            Expression::Identifier { location, .. } |
-                            Expression::BinaryExpression { location, .. } |
-                            Expression::AssignmentExpression { location, .. } |
-                            Expression::MemberAssignmentExpression { location, .. } |
-                            Expression::CallExpression { location, .. } |
-                            Expression::MemberCallExpression { location, .. } |  
-                            Expression::CallbackReference { location, .. } |
-                            Expression::ArrayExpression { location, .. } |
-                            Expression::ObjectExpression { location, .. } |
-                            Expression::MemberExpression { location, .. } |
-                            Expression::KeysOfExpression { location, .. } => location.clone(),
-                        };
+                        let object_loc = object_expr.location();
                         
-                        let location = SourceLocation::new(
-                            start_location.start_line,
-                            start_location.start_column,
-                            object_loc.end_line,
-                            object_loc.end_column
-                        );
+                        let location = start_location.merge(&object_loc);
                         
                         Ok(Expression::KeysOfExpression {
                             object: Box::new(object_expr),
@@ -774,10 +905,9 @@ impl<'a> Parser<'a> {
                     let id_name = name.clone();
                     self.advance();
                     
-                    if let Some(next_token) = self.current_token {
-                        if next_token.token == Token::OpenParen {
-                            return self.parse_function_call(id_name, start_location);
-                        }
+                    if let Some(next_token) = self.current_token
+                        && next_token.token == Token::OpenParen {
+                        return self.parse_function_call(id_name, start_location);
                     }
                     
                     Ok(Expression::Identifier { 
@@ -801,10 +931,19 @@ impl<'a> Parser<'a> {
                         location: start_location,
                     })
                 },
-                _ => Err(ParseError::UnexpectedToken(format!(
-                    "Unexpected token: {:?}",
-                    token_with_span.token
-                ), start_location)),
+                Token::BooleanLiteral(value) => {
+                    let bool_value = *value;
+                    self.advance();
+                    Ok(Expression::BooleanLiteral { 
+                        value: bool_value,
+                        location: start_location,
+                    })
+                },
+                Token::Null => {
+                    self.advance();
+                    Ok(Expression::NullLiteral { location: start_location })
+                },
+                _ => Err(ParseError::UnexpectedToken(self.source_code[token_with_span.span.clone()].to_string(), start_location)),
             },
             None => Err(ParseError::EndOfInput(start_location)),
         }
@@ -815,20 +954,14 @@ impl<'a> Parser<'a> {
         
         let mut arguments = Vec::new();
         
-        if let Some(token_with_span) = self.current_token {
-            if token_with_span.token == Token::CloseParen {
-                let end_location = self.current_location();
-                self.advance();
-                
-                let location = SourceLocation::new(
-                    start_location.start_line,
-                    start_location.start_column,
-                    end_location.end_line,
-                    end_location.end_column
-                );
-                
-                return Ok(Expression::CallExpression { callee, arguments, location });
-            }
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::CloseParen {
+            let end_location = self.current_location();
+            self.advance();
+            
+            let location = start_location.merge(&end_location);
+            
+            return Ok(Expression::CallExpression { callee, arguments, location });
         }
         
         arguments.push(self.parse_expression()?);
@@ -843,20 +976,15 @@ impl<'a> Parser<'a> {
                     let end_location = self.current_location();
                     self.advance();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        end_location.end_line,
-                        end_location.end_column
-                    );
+                    let location = start_location.merge(&end_location);
                     
                     return Ok(Expression::CallExpression { callee, arguments, location });
                 }
-                _ => return Err(ParseError::ExpectedToken("',' or ')'".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["','", "')'"])),
             }
         }
         
-        Err(ParseError::ExpectedToken("')'".to_string(), self.current_location()))
+        Err(self.expected_error(&["')'"]))
     }
 
     fn parse_callback_declaration(&mut self, start_location: SourceLocation) -> Result<Statement, ParseError> {
@@ -865,7 +993,7 @@ impl<'a> Parser<'a> {
         let name = match &self.current_token {
             Some(token_with_span) => match &token_with_span.token {
                 Token::Identifier(name) => name.clone(),
-                _ => return Err(ParseError::ExpectedToken("callback name".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["callback name"])),
             },
             None => return Err(ParseError::EndOfInput(self.current_location())),
         };
@@ -884,7 +1012,7 @@ impl<'a> Parser<'a> {
                             params.push(param.clone());
                             self.advance();
                         },
-                        _ => return Err(ParseError::ExpectedToken("parameter name".to_string(), self.current_location())),
+                        _ => return Err(self.expected_error(&["parameter name"])),
                     }
                 }
 
@@ -898,7 +1026,7 @@ impl<'a> Parser<'a> {
                                         params.push(param.clone());
                                         self.advance();
                                     },
-                                    _ => return Err(ParseError::ExpectedToken("parameter name".to_string(), self.current_location())),
+                                    _ => return Err(self.expected_error(&["parameter name"])),
                                 },
                                 None => return Err(ParseError::EndOfInput(self.current_location())),
                             }
@@ -907,7 +1035,7 @@ impl<'a> Parser<'a> {
                             self.advance();
                             break;
                         },
-                        _ => return Err(ParseError::ExpectedToken("',' or ')'".to_string(), self.current_location())),
+                        _ => return Err(self.expected_error(&["','", "')'"])),
                     }
                 }
             }
@@ -917,14 +1045,9 @@ impl<'a> Parser<'a> {
 
         let body = self.parse_block()?;
 
-        let end_location = body.location.clone();
+        let end_location = body.location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::CallbackDeclaration {
             name,
@@ -944,12 +1067,7 @@ impl<'a> Parser<'a> {
 
         let end_location = semicolon_location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::ReturnStatement { value, location })
     }
@@ -959,20 +1077,14 @@ impl<'a> Parser<'a> {
         
         let mut elements = Vec::new();
         
-        if let Some(token_with_span) = self.current_token {
-            if token_with_span.token == Token::CloseBracket {
-                let end_location = self.current_location();
-                self.advance();
-                
-                let location = SourceLocation::new(
-                    start_location.start_line,
-                    start_location.start_column,
-                    end_location.end_line,
-                    end_location.end_column
-                );
-                
-                return Ok(Expression::ArrayExpression { elements, location });
-            }
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::CloseBracket {
+            let end_location = self.current_location();
+            self.advance();
+            
+            let location = start_location.merge(&end_location);
+            
+            return Ok(Expression::ArrayExpression { elements, location });
         }
         
         elements.push(self.parse_expression()?);
@@ -987,20 +1099,15 @@ impl<'a> Parser<'a> {
                     let end_location = self.current_location();
                     self.advance();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        end_location.end_line,
-                        end_location.end_column
-                    );
+                    let location = start_location.merge(&end_location);
                     
                     return Ok(Expression::ArrayExpression { elements, location });
                 }
-                _ => return Err(ParseError::ExpectedToken("',' or ']'".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["','", "']'"])),
             }
         }
         
-        Err(ParseError::ExpectedToken("']'".to_string(), self.current_location()))
+        Err(self.expected_error(&["']'"]))
     }
     
     fn parse_object_literal(&mut self, start_location: SourceLocation) -> Result<Expression, ParseError> {
@@ -1008,20 +1115,14 @@ impl<'a> Parser<'a> {
         
         let mut properties = Vec::new();
         
-        if let Some(token_with_span) = self.current_token {
-            if token_with_span.token == Token::CloseBrace {
-                let end_location = self.current_location();
-                self.advance();
-                
-                let location = SourceLocation::new(
-                    start_location.start_line,
-                    start_location.start_column,
-                    end_location.end_line,
-                    end_location.end_column
-                );
-                
-                return Ok(Expression::ObjectExpression { properties, location });
-            }
+        if let Some(token_with_span) = self.current_token
+            && token_with_span.token == Token::CloseBrace {
+            let end_location = self.current_location();
+            self.advance();
+            
+            let location = start_location.merge(&end_location);
+            
+            return Ok(Expression::ObjectExpression { properties, location });
         }
         
         let property = self.parse_object_property()?;
@@ -1038,20 +1139,15 @@ impl<'a> Parser<'a> {
                     let end_location = self.current_location();
                     self.advance();
                     
-                    let location = SourceLocation::new(
-                        start_location.start_line,
-                        start_location.start_column,
-                        end_location.end_line,
-                        end_location.end_column
-                    );
+                    let location = start_location.merge(&end_location);
                     
                     return Ok(Expression::ObjectExpression { properties, location });
                 }
-                _ => return Err(ParseError::ExpectedToken("',' or '}'".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["','", "'}'"])),
             }
         }
         
-        Err(ParseError::ExpectedToken("'}'".to_string(), self.current_location()))
+        Err(self.expected_error(&["'}'"]))
     }
     
     fn parse_object_property(&mut self) -> Result<Property, ParseError> {
@@ -1061,7 +1157,7 @@ impl<'a> Parser<'a> {
             Some(token_with_span) => match &token_with_span.token {
                 Token::Identifier(name) => name.clone(),
                 Token::StringLiteral(value) => value.clone(),
-                _ => return Err(ParseError::ExpectedToken("property key".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["property key"])),
             },
             None => return Err(ParseError::EndOfInput(self.current_location())),
         };
@@ -1071,105 +1167,79 @@ impl<'a> Parser<'a> {
         
         let value = self.parse_expression()?;
         
-        let end_location = match &value {
-            Expression::StringLiteral { location, .. } |
-            Expression::NumberLiteral { location, .. } |
-            Expression::Identifier { location, .. } |
-            Expression::BinaryExpression { location, .. } |
-            Expression::AssignmentExpression { location, .. } |
-            Expression::MemberAssignmentExpression { location, .. } |
-            Expression::CallExpression { location, .. } |
-            Expression::MemberCallExpression { location, .. } |  
-            Expression::CallbackReference { location, .. } |
-            Expression::ArrayExpression { location, .. } |
-            Expression::ObjectExpression { location, .. } |
-            Expression::MemberExpression { location, .. } |
-            Expression::KeysOfExpression { location, .. } => location.clone(),
-        };
+        let end_location = value.location();
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
         
         Ok(Property::new(key, value, location))
     }
 
     fn parse_member_access(&mut self, mut object: Expression) -> Result<Expression, ParseError> {
-        loop {
-            match &self.current_token {
-                Some(token_with_span) => match &token_with_span.token {
-                    Token::Dot => {
-                        if !self.flags.allow_object_navigation {
-                            return Err(ParseError::FeatureDisabled("Object navigation (dot notation)".to_string(), self.current_location()));
-                        }
-                        
-                        self.advance();
-                        
-                        match &self.current_token {
-                            Some(token_with_span) => match &token_with_span.token {
-                                Token::Identifier(prop_name) => {
-                                    let property = prop_name.clone();
-                                    let property_location = self.current_location();
-                                    self.advance();
-                                    
-                                    
-                                    let obj_start_line = match &object {
-                                        Expression::StringLiteral { location, .. } |
-                                        Expression::NumberLiteral { location, .. } |
-                                        Expression::Identifier { location, .. } |
-                                        Expression::BinaryExpression { location, .. } |
-                                        Expression::AssignmentExpression { location, .. } |
-                                        Expression::MemberAssignmentExpression { location, .. } |
-                                        Expression::CallExpression { location, .. } |
-                                        Expression::MemberCallExpression { location, .. } |  
-                                        Expression::CallbackReference { location, .. } |
-                                        Expression::ArrayExpression { location, .. } |
-                                        Expression::ObjectExpression { location, .. } |
-                                        Expression::MemberExpression { location, .. } |
-                                        Expression::KeysOfExpression { location, .. } => location.start_line,
-                                    };
-                                    
-                                    let obj_start_column = match &object {
-                                        Expression::StringLiteral { location, .. } |
-                                        Expression::NumberLiteral { location, .. } |
-                                        Expression::Identifier { location, .. } |
-                                        Expression::BinaryExpression { location, .. } |
-                                        Expression::AssignmentExpression { location, .. } |
-                                        Expression::MemberAssignmentExpression { location, .. } |
-                                        Expression::CallExpression { location, .. } |
-                                        Expression::MemberCallExpression { location, .. } |  
-                                        Expression::CallbackReference { location, .. } |
-                                        Expression::ArrayExpression { location, .. } |
-                                        Expression::ObjectExpression { location, .. } |
-                                        Expression::MemberExpression { location, .. } |
-                                        Expression::KeysOfExpression { location, .. } => location.start_column,
-                                    };
-                                    
-                                    
-                                    if let Some(token_with_span) = self.current_token {
-                                        if token_with_span.token == Token::OpenParen {
+        while let Some(token_with_span) = self.current_token {
+            match &token_with_span.token {
+                Token::Dot => {
+                    if !self.flags.allow_object_navigation {
+                        return Err(ParseError::FeatureDisabled("Object navigation (dot notation)".to_string(), self.current_location()));
+                    }
+                    
+                    self.advance();
+                    
+                    match &self.current_token {
+                        Some(token_with_span) => match &token_with_span.token {
+                            Token::Identifier(prop_name) => {
+                                let property = prop_name.clone();
+                                let property_location = self.current_location();
+                                self.advance();
+                                
+                                
+                                let obj_location = object.location();
+                                
+                                
+                                if let Some(token_with_span) = self.current_token {
+                                    if token_with_span.token == Token::OpenParen {
+                                        self.advance(); 
+                                        
+                                        
+                                        let mut arguments = Vec::new();
+                                        
+                                        
+                                        if let Some(token_with_span) = self.current_token
+                                            && token_with_span.token == Token::CloseParen {
+                                            
+                                            let end_call_location = self.current_location();
                                             self.advance(); 
                                             
                                             
-                                            let mut arguments = Vec::new();
+                                            let call_location = obj_location.merge(&end_call_location);
                                             
                                             
-                                            if let Some(token_with_span) = self.current_token {
-                                                if token_with_span.token == Token::CloseParen {
-                                                    
+                                            object = Expression::MemberCallExpression {
+                                                object: Box::new(object),
+                                                property: Some(property),
+                                                property_expr: None,
+                                                computed: false,
+                                                arguments,
+                                                location: call_location,
+                                            };
+                                            continue;
+                                        }
+                                        
+                                        
+                                        arguments.push(self.parse_expression()?);
+                                        
+                                        
+                                        while let Some(token_with_span) = self.current_token {
+                                            match &token_with_span.token {
+                                                Token::Comma => {
+                                                    self.advance(); 
+                                                    arguments.push(self.parse_expression()?);
+                                                }
+                                                Token::CloseParen => {
                                                     let end_call_location = self.current_location();
                                                     self.advance(); 
                                                     
                                                     
-                                                    let call_location = SourceLocation::new(
-                                                        obj_start_line,
-                                                        obj_start_column,
-                                                        end_call_location.end_line,
-                                                        end_call_location.end_column
-                                                    );
+                                                    let call_location = obj_location.merge(&end_call_location);
                                                     
                                                     
                                                     object = Expression::MemberCallExpression {
@@ -1180,71 +1250,14 @@ impl<'a> Parser<'a> {
                                                         arguments,
                                                         location: call_location,
                                                     };
-                                                    continue;
+                                                    break;
                                                 }
+                                                _ => return Err(self.expected_error(&["','", "')'"])),
                                             }
-                                            
-                                            
-                                            arguments.push(self.parse_expression()?);
-                                            
-                                            
-                                            while let Some(token_with_span) = self.current_token {
-                                                match &token_with_span.token {
-                                                    Token::Comma => {
-                                                        self.advance(); 
-                                                        arguments.push(self.parse_expression()?);
-                                                    }
-                                                    Token::CloseParen => {
-                                                        let end_call_location = self.current_location();
-                                                        self.advance(); 
-                                                        
-                                                        
-                                                        let call_location = SourceLocation::new(
-                                                            obj_start_line,
-                                                            obj_start_column,
-                                                            end_call_location.end_line,
-                                                            end_call_location.end_column
-                                                        );
-                                                        
-                                                        
-                                                        object = Expression::MemberCallExpression {
-                                                            object: Box::new(object),
-                                                            property: Some(property),
-                                                            property_expr: None,
-                                                            computed: false,
-                                                            arguments,
-                                                            location: call_location,
-                                                        };
-                                                        break;
-                                                    }
-                                                    _ => return Err(ParseError::ExpectedToken("',' or ')'".to_string(), self.current_location())),
-                                                }
-                                            }
-                                        } else {
-                                            
-                                            let member_expr_location = SourceLocation::new(
-                                                obj_start_line,
-                                                obj_start_column,
-                                                property_location.end_line,
-                                                property_location.end_column
-                                            );
-                                            
-                                            object = Expression::MemberExpression {
-                                                object: Box::new(object),
-                                                property: Some(property),
-                                                property_expr: None,
-                                                computed: false,
-                                                location: member_expr_location,
-                                            };
                                         }
                                     } else {
                                         
-                                        let member_expr_location = SourceLocation::new(
-                                            obj_start_line,
-                                            obj_start_column,
-                                            property_location.end_line,
-                                            property_location.end_column
-                                        );
+                                        let member_expr_location = obj_location.merge(&property_location);
                                         
                                         object = Expression::MemberExpression {
                                             object: Box::new(object),
@@ -1254,83 +1267,82 @@ impl<'a> Parser<'a> {
                                             location: member_expr_location,
                                         };
                                     }
+                                } else {
+                                    
+                                    let member_expr_location = obj_location.merge(&property_location);
+                                    
+                                    object = Expression::MemberExpression {
+                                        object: Box::new(object),
+                                        property: Some(property),
+                                        property_expr: None,
+                                        computed: false,
+                                        location: member_expr_location,
+                                    };
                                 }
-                                _ => return Err(ParseError::ExpectedToken("property name".to_string(), self.current_location())),
-                            },
-                            None => return Err(ParseError::EndOfInput(self.current_location())),
-                        }
-                    },
+                            }
+                            _ => return Err(self.expected_error(&["property name"])),
+                        },
+                        None => return Err(ParseError::EndOfInput(self.current_location())),
+                    }
+                },
+                
+                Token::OpenBracket => {
+                    if !self.flags.allow_object_navigation {
+                        return Err(ParseError::FeatureDisabled("Object navigation (bracket notation)".to_string(), self.current_location()));
+                    }
                     
-                    Token::OpenBracket => {
-                        if !self.flags.allow_object_navigation {
-                            return Err(ParseError::FeatureDisabled("Object navigation (bracket notation)".to_string(), self.current_location()));
-                        }
-                        
-                        self.advance();
-                        
-                        let property_expr = self.parse_expression()?;
-                        
-                        let close_bracket_location = self.current_location();
-                        self.expect(Token::CloseBracket)?;
-                        
-                        let obj_start_line = match &object {
-                            Expression::StringLiteral { location, .. } |
-                            Expression::NumberLiteral { location, .. } |
-                            Expression::Identifier { location, .. } |
-                            Expression::BinaryExpression { location, .. } |
-                            Expression::AssignmentExpression { location, .. } |
-                            Expression::MemberAssignmentExpression { location, .. } |
-                            Expression::CallExpression { location, .. } |
-                            Expression::MemberCallExpression { location, .. } |  
-                            Expression::CallbackReference { location, .. } |
-                            Expression::ArrayExpression { location, .. } |
-                            Expression::ObjectExpression { location, .. } |
-                            Expression::MemberExpression { location, .. } |
-                            Expression::KeysOfExpression { location, .. } => location.start_line,
-                        };
-                        
-                        let obj_start_column = match &object {
-                            Expression::StringLiteral { location, .. } |
-                            Expression::NumberLiteral { location, .. } |
-                            Expression::Identifier { location, .. } |
-                            Expression::BinaryExpression { location, .. } |
-                            Expression::AssignmentExpression { location, .. } |
-                            Expression::MemberAssignmentExpression { location, .. } |
-                            Expression::CallExpression { location, .. } |
-                            Expression::MemberCallExpression { location, .. } |  
-                            Expression::CallbackReference { location, .. } |
-                            Expression::ArrayExpression { location, .. } |
-                            Expression::ObjectExpression { location, .. } |
-                            Expression::MemberExpression { location, .. } |
-                            Expression::KeysOfExpression { location, .. } => location.start_column,
-                        };
-                        
-                        let member_expr_location = SourceLocation::new(
-                            obj_start_line,
-                            obj_start_column,
-                            close_bracket_location.end_line,
-                            close_bracket_location.end_column
-                        );
-                        
-                        if let Some(token_with_span) = self.current_token {
-                            if token_with_span.token == Token::OpenParen {
-                                
+                    self.advance();
+                    
+                    let property_expr = self.parse_expression()?;
+                    
+                    let close_bracket_location = self.current_location();
+                    self.expect(Token::CloseBracket)?;
+                    
+                    let obj_location = object.location();
+                    
+                    let member_expr_location = obj_location.merge(&close_bracket_location);
+                    
+                    if let Some(token_with_span) = self.current_token {
+                        if token_with_span.token == Token::OpenParen {
+                            
+                            self.advance();
+                            
+                            let mut arguments = Vec::new();
+                            
+                            
+                            if let Some(token_with_span) = self.current_token
+                                && token_with_span.token == Token::CloseParen {
+                                let end_call_location = self.current_location();
                                 self.advance();
                                 
-                                let mut arguments = Vec::new();
+                                let call_location = obj_location.merge(&end_call_location);
                                 
                                 
-                                if let Some(token_with_span) = self.current_token {
-                                    if token_with_span.token == Token::CloseParen {
+                                object = Expression::MemberCallExpression {
+                                    object: Box::new(object),
+                                    property: None,
+                                    property_expr: Some(Box::new(property_expr)),
+                                    computed: true,
+                                    arguments,
+                                    location: call_location,
+                                };
+                                continue;
+                            }
+                            
+                            
+                            arguments.push(self.parse_expression()?);
+                            
+                            while let Some(token_with_span) = self.current_token {
+                                match &token_with_span.token {
+                                    Token::Comma => {
+                                        self.advance();
+                                        arguments.push(self.parse_expression()?);
+                                    }
+                                    Token::CloseParen => {
                                         let end_call_location = self.current_location();
                                         self.advance();
                                         
-                                        let call_location = SourceLocation::new(
-                                            obj_start_line,
-                                            obj_start_column,
-                                            end_call_location.end_line,
-                                            end_call_location.end_column
-                                        );
+                                        let call_location = obj_location.merge(&end_call_location);
                                         
                                         
                                         object = Expression::MemberCallExpression {
@@ -1341,53 +1353,10 @@ impl<'a> Parser<'a> {
                                             arguments,
                                             location: call_location,
                                         };
-                                        continue;
-                                    }
-                                }
-                                
-                                
-                                arguments.push(self.parse_expression()?);
-                                
-                                while let Some(token_with_span) = self.current_token {
-                                    match &token_with_span.token {
-                                        Token::Comma => {
-                                            self.advance();
-                                            arguments.push(self.parse_expression()?);
-                                        }
-                                        Token::CloseParen => {
-                                            let end_call_location = self.current_location();
-                                            self.advance();
-                                            
-                                            let call_location = SourceLocation::new(
-                                                obj_start_line,
-                                                obj_start_column,
-                                                end_call_location.end_line,
-                                                end_call_location.end_column
-                                            );
-                                            
-                                            
-                                            object = Expression::MemberCallExpression {
-                                                object: Box::new(object),
-                                                property: None,
-                                                property_expr: Some(Box::new(property_expr)),
-                                                computed: true,
-                                                arguments,
-                                                location: call_location,
-                                            };
-                                            break;
-                                        }
-                                        _ => return Err(ParseError::ExpectedToken("',' or ')'".to_string(), self.current_location())),
+                                        break;
                                     }
+                                    _ => return Err(self.expected_error(&["','", "')'"])),
                                 }
-                            } else {
-                                
-                                object = Expression::MemberExpression {
-                                    object: Box::new(object),
-                                    property: None,
-                                    property_expr: Some(Box::new(property_expr)),
-                                    computed: true,
-                                    location: member_expr_location,
-                                };
                             }
                         } else {
                             
@@ -1399,10 +1368,18 @@ impl<'a> Parser<'a> {
                                 location: member_expr_location,
                             };
                         }
-                    },
-                    _ => break,
+                    } else {
+                        
+                        object = Expression::MemberExpression {
+                            object: Box::new(object),
+                            property: None,
+                            property_expr: Some(Box::new(property_expr)),
+                            computed: true,
+                            location: member_expr_location,
+                        };
+                    }
                 },
-                None => break,
+                _ => break,
             }
         }
         Ok(object)
@@ -1414,7 +1391,7 @@ impl<'a> Parser<'a> {
         let variable = match &self.current_token {
             Some(token_with_span) => match &token_with_span.token {
                 Token::Identifier(name) => name.clone(),
-                _ => return Err(ParseError::ExpectedToken("identifier".to_string(), self.current_location())),
+                _ => return Err(self.expected_error(&["identifier"])),
             },
             None => return Err(ParseError::EndOfInput(self.current_location())),
         };
@@ -1423,7 +1400,7 @@ impl<'a> Parser<'a> {
         match &self.current_token {
             Some(token_with_span) => {
                 if token_with_span.token != Token::In {
-                    return Err(ParseError::ExpectedToken("'in'".to_string(), self.current_location()));
+                    return Err(self.expected_error(&["'in'"]));
                 }
                 self.advance();
             },
@@ -1434,14 +1411,9 @@ impl<'a> Parser<'a> {
         
         let body = self.parse_block()?;
         
-        let end_location = body.location.clone();
+        let end_location = body.location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::LoopStatement {
             variable,
@@ -1459,12 +1431,7 @@ impl<'a> Parser<'a> {
 
         let end_location = semicolon_location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::EndStatement { location })
     }
@@ -1477,13 +1444,58 @@ impl<'a> Parser<'a> {
 
         let end_location = semicolon_location;
         
-        let location = SourceLocation::new(
-            start_location.start_line,
-            start_location.start_column,
-            end_location.end_line,
-            end_location.end_column
-        );
+        let location = start_location.merge(&end_location);
 
         Ok(Statement::ContinueStatement { location })
     }
 }
+
+fn starts_statement(token: &Token) -> bool {
+    matches!(token,
+        Token::Vl |
+        Token::If |
+        Token::Cb |
+        Token::Res |
+        Token::Loop |
+        Token::End |
+        Token::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+
+    fn parse_with_diagnostics(code: &str) -> (Program, Vec<ParseError>) {
+        let tokens = lexer::tokenize(code).expect("test script lexes");
+        Parser::new(&tokens, FeatureFlags::all_enabled(), code).parse_program_with_diagnostics()
+    }
+
+    #[test]
+    fn recovery_resumes_at_statement_after_skipped_block() {
+        let (program, errors) = parse_with_diagnostics("cb f( { res 1; } vl a = 1;");
+        assert_eq!(errors.len(), 1);
+        assert!(program.statements.iter().any(|statement| matches!(statement, Statement::VariableDeclaration { name, .. } if name == "a")));
+    }
+
+    #[test]
+    fn recovery_continues_through_else_after_skipped_block() {
+        let (program, errors) = parse_with_diagnostics("if ( { res 1; } else { res 2; } vl a = 1;");
+        assert_eq!(errors.len(), 1);
+        assert!(program.statements.iter().any(|statement| matches!(statement, Statement::VariableDeclaration { name, .. } if name == "a")));
+    }
+
+    #[test]
+    fn recovery_stops_at_statement_after_unclosed_object_literal() {
+        let (program, errors) = parse_with_diagnostics("vl x = {a: ; vl y = 1; vl z = ;");
+        assert_eq!(errors.len(), 2);
+        assert!(program.statements.iter().any(|statement| matches!(statement, Statement::VariableDeclaration { name, .. } if name == "y")));
+    }
+
+    #[test]
+    fn recovery_skips_closed_object_literal() {
+        let (program, errors) = parse_with_diagnostics("vl x = {a: , b: {c: 1}}; vl y = 1;");
+        assert_eq!(errors.len(), 1);
+        assert!(program.statements.iter().any(|statement| matches!(statement, Statement::VariableDeclaration { name, .. } if name == "y")));
+    }
+}
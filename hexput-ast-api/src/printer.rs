@@ -0,0 +1,529 @@
+use crate::ast_structs::{Block, Expression, Operator, Program, SourceLocation, Statement};
+
+const INDENT: &str = "    ";
+
+const KEYWORDS: &[&str] = &[
+    "vl", "if", "else", "cb", "res", "loop", "in", "end", "continue", "keysof", "true", "false", "null",
+];
+
+const PREC_ASSIGNMENT: u8 = 1;
+const PREC_OR: u8 = 2;
+const PREC_AND: u8 = 3;
+const PREC_EQUALITY: u8 = 4;
+const PREC_COMPARISON: u8 = 5;
+const PREC_ADDITIVE: u8 = 6;
+const PREC_MULTIPLICATIVE: u8 = 7;
+const PREC_UNARY: u8 = 8;
+const PREC_POSTFIX: u8 = 9;
+const PREC_PRIMARY: u8 = 10;
+
+pub fn to_source(program: &Program) -> String {
+    let mut printer = Printer::new();
+    for statement in &program.statements {
+        printer.print_statement(statement);
+    }
+    printer.output
+}
+
+pub fn to_source_with_comments(program: &Program, source: &str, comments: Vec<(String, SourceLocation)>) -> String {
+    let mut printer = Printer::new();
+    printer.source = source.to_string();
+    printer.comments = comments;
+    printer.preserve_layout = true;
+    for statement in &program.statements {
+        printer.print_statement(statement);
+    }
+    printer.flush_comments_before(usize::MAX);
+    printer.output
+}
+
+pub fn statement_to_source(statement: &Statement) -> String {
+    let mut printer = Printer::new();
+    printer.print_statement(statement);
+    printer.output
+}
+
+pub fn expression_to_source(expression: &Expression) -> String {
+    let mut printer = Printer::new();
+    printer.print_expression(expression, PREC_ASSIGNMENT);
+    printer.output
+}
+
+struct Printer {
+    output: String,
+    indent: usize,
+    source: String,
+    comments: Vec<(String, SourceLocation)>,
+    next_comment: usize,
+    preserve_layout: bool,
+    last_line: usize,
+}
+
+impl Printer {
+    fn new() -> Self {
+        Self {
+            output: String::new(),
+            indent: 0,
+            source: String::new(),
+            comments: Vec::new(),
+            next_comment: 0,
+            preserve_layout: false,
+            last_line: 0,
+        }
+    }
+
+    fn has_comment_before(&self, offset: usize) -> bool {
+        self.comments.get(self.next_comment).is_some_and(|(_, location)| location.span.start < offset)
+    }
+
+    fn flush_comments_before(&mut self, offset: usize) {
+        while self.has_comment_before(offset) {
+            let (text, location) = self.comments[self.next_comment].clone();
+            self.separate_from_previous(location.start_line);
+            self.write_indent();
+            self.output.push_str(&text);
+            self.output.push('\n');
+            self.last_line = location.end_line;
+            self.next_comment += 1;
+        }
+    }
+
+    fn print_trailing_comment(&mut self, statement_location: &SourceLocation) {
+        if let Some((text, location)) = self.comments.get(self.next_comment)
+            && location.start_line == statement_location.end_line
+            && location.span.start >= statement_location.span.end {
+            self.output.push(' ');
+            self.output.push_str(text);
+            self.next_comment += 1;
+        }
+    }
+
+    fn print_source_if_commented(&mut self, start: usize, end: usize) -> bool {
+        if !self.has_comment_before(end) {
+            return false;
+        }
+        let Some(text) = self.source.get(start..end) else {
+            return false;
+        };
+        self.output.push_str(text.trim_end());
+        while self.has_comment_before(end) {
+            self.next_comment += 1;
+        }
+        true
+    }
+
+    fn separate_from_previous(&mut self, line: usize) {
+        if self.preserve_layout && self.last_line > 0 && line > self.last_line + 1 {
+            self.output.push('\n');
+        }
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent {
+            self.output.push_str(INDENT);
+        }
+    }
+
+    fn print_statement(&mut self, statement: &Statement) {
+        let location = statement.location();
+        self.flush_comments_before(location.span.start);
+        self.separate_from_previous(location.start_line);
+        self.write_indent();
+        if has_body(statement) || !self.print_source_if_commented(location.span.start, location.span.end) {
+            self.print_statement_body(statement);
+        }
+        self.print_trailing_comment(&location);
+        self.output.push('\n');
+        self.last_line = location.end_line;
+    }
+
+    fn print_statement_body(&mut self, statement: &Statement) {
+        match statement {
+            Statement::VariableDeclaration { name, value, .. } => {
+                self.output.push_str(&format!("vl {} = ", name));
+                self.print_expression(value, PREC_ASSIGNMENT);
+                self.output.push(';');
+            },
+            Statement::ExpressionStatement { expression, .. } => {
+                if starts_with_object(expression) {
+                    self.output.push('(');
+                    self.print_expression(expression, PREC_ASSIGNMENT);
+                    self.output.push(')');
+                } else {
+                    self.print_expression(expression, PREC_ASSIGNMENT);
+                }
+                self.output.push(';');
+            },
+            Statement::IfStatement { condition, body, else_body, location } => {
+                if !self.print_source_if_commented(location.span.start, body.location.span.start) {
+                    self.output.push_str("if ");
+                    self.print_expression(condition, PREC_ASSIGNMENT);
+                }
+                self.output.push(' ');
+                let body_end = else_body.as_ref().map_or(body.location.span.end, |else_body| else_body.location.span.start);
+                self.print_block_until(body, body_end);
+
+                if let Some(else_body) = else_body {
+                    self.output.push_str(" else ");
+                    match else_body.statements.as_slice() {
+                        [else_if @ Statement::IfStatement { .. }] if !self.has_comment_before(else_if.location().span.start) => {
+                            self.print_statement_body(else_if);
+                        },
+                        _ => self.print_block(else_body),
+                    }
+                }
+            },
+            Statement::Block { block, .. } => self.print_block(block),
+            Statement::CallbackDeclaration { name, params, body, location } => {
+                if !self.print_source_if_commented(location.span.start, body.location.span.start) {
+                    self.output.push_str(&format!("cb {}({})", name, params.join(", ")));
+                }
+                self.output.push(' ');
+                self.print_block(body);
+            },
+            Statement::ReturnStatement { value, .. } => {
+                self.output.push_str("res ");
+                self.print_expression(value, PREC_ASSIGNMENT);
+                self.output.push(';');
+            },
+            Statement::LoopStatement { variable, iterable, body, location } => {
+                if !self.print_source_if_commented(location.span.start, body.location.span.start) {
+                    self.output.push_str(&format!("loop {} in ", variable));
+                    self.print_expression(iterable, PREC_ASSIGNMENT);
+                }
+                self.output.push(' ');
+                self.print_block(body);
+            },
+            Statement::EndStatement { .. } => self.output.push_str("end;"),
+            Statement::ContinueStatement { .. } => self.output.push_str("continue;"),
+            Statement::ErrorStatement { message, .. } => {
+                self.output.push_str(&format!("// error: {}", message));
+            },
+        }
+    }
+
+    fn print_block(&mut self, block: &Block) {
+        self.print_block_until(block, block.location.span.end);
+    }
+
+    fn print_block_until(&mut self, block: &Block, end: usize) {
+        if block.statements.is_empty() && !self.has_comment_before(end) {
+            self.output.push_str("{}");
+            return;
+        }
+
+        self.output.push_str("{\n");
+        self.indent += 1;
+        self.last_line = 0;
+        for statement in &block.statements {
+            self.print_statement(statement);
+        }
+        self.flush_comments_before(block.location.span.end);
+        self.last_line = 0;
+        self.flush_comments_before(end);
+        self.indent -= 1;
+        self.write_indent();
+        self.output.push('}');
+    }
+
+    fn print_expression(&mut self, expression: &Expression, min_precedence: u8) {
+        let needs_parens = precedence(expression) < min_precedence;
+        if needs_parens {
+            self.output.push('(');
+        }
+
+        match expression {
+            Expression::StringLiteral { value, .. } => self.output.push_str(&quote_string(value)),
+            Expression::NumberLiteral { value, .. } => self.output.push_str(&value.to_string()),
+            Expression::BooleanLiteral { value, .. } => self.output.push_str(&value.to_string()),
+            Expression::NullLiteral { .. } => self.output.push_str("null"),
+            Expression::Identifier { name, .. } |
+            Expression::CallbackReference { name, .. } => self.output.push_str(name),
+            Expression::BinaryExpression { left, operator, right, .. } |
+            Expression::LogicalExpression { left, operator, right, .. } => {
+                let operator_precedence = binary_precedence(operator);
+                self.print_expression(left, operator_precedence);
+                self.output.push_str(&format!(" {} ", operator_symbol(operator)));
+                self.print_expression(right, operator_precedence + 1);
+            },
+            Expression::UnaryExpression { operator, operand, .. } => {
+                self.output.push_str(operator_symbol(operator));
+                self.print_expression(operand, PREC_UNARY);
+            },
+            Expression::AssignmentExpression { target, value, .. } => {
+                self.output.push_str(&format!("{} = ", target));
+                self.print_expression(value, PREC_OR);
+            },
+            Expression::MemberAssignmentExpression { object, property, property_expr, value, .. } => {
+                self.print_member(object, property, property_expr);
+                self.output.push_str(" = ");
+                self.print_expression(value, PREC_OR);
+            },
+            Expression::CallExpression { callee, arguments, .. } => {
+                self.output.push_str(callee);
+                self.print_arguments(arguments);
+            },
+            Expression::MemberCallExpression { object, property, property_expr, arguments, .. } => {
+                self.print_member(object, property, property_expr);
+                self.print_arguments(arguments);
+            },
+            Expression::ArrayExpression { elements, .. } => {
+                self.output.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.output.push_str(", ");
+                    }
+                    self.print_expression(element, PREC_ASSIGNMENT);
+                }
+                self.output.push(']');
+            },
+            Expression::ObjectExpression { properties, .. } => {
+                if properties.is_empty() {
+                    self.output.push_str("{}");
+                } else {
+                    self.output.push_str("{ ");
+                    for (i, property) in properties.iter().enumerate() {
+                        if i > 0 {
+                            self.output.push_str(", ");
+                        }
+                        self.output.push_str(&format!("{}: ", format_key(&property.key)));
+                        self.print_expression(&property.value, PREC_ASSIGNMENT);
+                    }
+                    self.output.push_str(" }");
+                }
+            },
+            Expression::MemberExpression { object, property, property_expr, .. } => {
+                self.print_member(object, property, property_expr);
+            },
+            Expression::KeysOfExpression { object, .. } => {
+                self.output.push_str("keysof ");
+                self.print_expression(object, PREC_PRIMARY);
+            },
+        }
+
+        if needs_parens {
+            self.output.push(')');
+        }
+    }
+
+    fn print_member(&mut self, object: &Expression, property: &Option<String>, property_expr: &Option<Box<Expression>>) {
+        self.print_expression(object, PREC_POSTFIX);
+        match (property, property_expr) {
+            (_, Some(property_expr)) => {
+                self.output.push('[');
+                self.print_expression(property_expr, PREC_ASSIGNMENT);
+                self.output.push(']');
+            },
+            (Some(property), None) if is_identifier(property) => {
+                self.output.push('.');
+                self.output.push_str(property);
+            },
+            (Some(property), None) => {
+                self.output.push_str(&format!("[{}]", quote_string(property)));
+            },
+            (None, None) => {},
+        }
+    }
+
+    fn print_arguments(&mut self, arguments: &[Expression]) {
+        self.output.push('(');
+        for (i, argument) in arguments.iter().enumerate() {
+            if i > 0 {
+                self.output.push_str(", ");
+            }
+            self.print_expression(argument, PREC_ASSIGNMENT);
+        }
+        self.output.push(')');
+    }
+}
+
+fn has_body(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::IfStatement { .. } | Statement::LoopStatement { .. } | Statement::CallbackDeclaration { .. } | Statement::Block { .. }
+    )
+}
+
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::AssignmentExpression { .. } |
+        Expression::MemberAssignmentExpression { .. } => PREC_ASSIGNMENT,
+        Expression::BinaryExpression { operator, .. } |
+        Expression::LogicalExpression { operator, .. } => binary_precedence(operator),
+        Expression::UnaryExpression { .. } => PREC_UNARY,
+        Expression::NumberLiteral { value, .. } if value.is_sign_negative() => PREC_UNARY,
+        Expression::MemberExpression { .. } |
+        Expression::MemberCallExpression { .. } |
+        Expression::KeysOfExpression { .. } => PREC_POSTFIX,
+        Expression::StringLiteral { .. } |
+        Expression::NumberLiteral { .. } |
+        Expression::BooleanLiteral { .. } |
+        Expression::NullLiteral { .. } |
+        Expression::Identifier { .. } |
+        Expression::CallbackReference { .. } |
+        Expression::CallExpression { .. } |
+        Expression::ArrayExpression { .. } |
+        Expression::ObjectExpression { .. } => PREC_PRIMARY,
+    }
+}
+
+fn binary_precedence(operator: &Operator) -> u8 {
+    match operator {
+        Operator::Or => PREC_OR,
+        Operator::And => PREC_AND,
+        Operator::Equal | Operator::NotEqual => PREC_EQUALITY,
+        Operator::Greater | Operator::Less | Operator::GreaterEqual | Operator::LessEqual => PREC_COMPARISON,
+        Operator::Plus | Operator::Minus => PREC_ADDITIVE,
+        Operator::Multiply | Operator::Divide => PREC_MULTIPLICATIVE,
+        Operator::Not => PREC_UNARY,
+    }
+}
+
+fn operator_symbol(operator: &Operator) -> &'static str {
+    match operator {
+        Operator::Equal => "==",
+        Operator::NotEqual => "!=",
+        Operator::Plus => "+",
+        Operator::Minus => "-",
+        Operator::Multiply => "*",
+        Operator::Divide => "/",
+        Operator::Greater => ">",
+        Operator::Less => "<",
+        Operator::GreaterEqual => ">=",
+        Operator::LessEqual => "<=",
+        Operator::And => "&&",
+        Operator::Or => "||",
+        Operator::Not => "!",
+    }
+}
+
+fn starts_with_object(expression: &Expression) -> bool {
+    match expression {
+        Expression::ObjectExpression { .. } => true,
+        Expression::BinaryExpression { left, .. } |
+        Expression::LogicalExpression { left, .. } => precedence(left) >= precedence(expression) && starts_with_object(left),
+        Expression::MemberExpression { object, .. } |
+        Expression::MemberCallExpression { object, .. } |
+        Expression::MemberAssignmentExpression { object, .. } => precedence(object) >= PREC_POSTFIX && starts_with_object(object),
+        _ => false,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let starts_well = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    starts_well && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && !KEYWORDS.contains(&name)
+}
+
+fn format_key(key: &str) -> String {
+    if is_identifier(key) {
+        key.to_string()
+    } else {
+        quote_string(key)
+    }
+}
+
+fn quote_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlags;
+    use crate::lexer;
+    use crate::parser::Parser;
+
+    fn format(code: &str) -> String {
+        crate::format_source(code, FeatureFlags::all_enabled()).expect("test script parses")
+    }
+
+    fn parse(code: &str) -> Program {
+        let tokens = lexer::tokenize(code).expect("test script lexes");
+        Parser::new(&tokens, FeatureFlags::all_enabled(), code).parse_program().expect("test script parses")
+    }
+
+    fn assert_round_trip(code: &str) {
+        let program = parse(code);
+        let printed = to_source(&program);
+        let reparsed = parse(&printed);
+        assert_eq!(
+            crate::to_json_string(&reparsed, false).unwrap(),
+            crate::to_json_string(&program, false).unwrap(),
+            "printed source:\n{}",
+            printed,
+        );
+    }
+
+    #[test]
+    fn statements_round_trip() {
+        assert_round_trip("vl a = 1; a = 2; f(a);");
+        assert_round_trip("if a { b; } else if c { d; } else { e; }");
+        assert_round_trip("if a { } { vl f = 1; g(f); }");
+        assert_round_trip("cb g(x, y) { res x; } cb h() { }");
+        assert_round_trip("loop i in [1, 2] { if i { end; } continue; } res null;");
+    }
+
+    #[test]
+    fn expressions_round_trip() {
+        assert_round_trip(r#"vl s = "q\"uote\\ \n\t";"#);
+        assert_round_trip("vl n = [0, 1.5, 1000000, true, false, null];");
+        assert_round_trip(r#"vl o = {a: 1, "b c": [1, {}], d: {e: f}};"#);
+        assert_round_trip(r#"res o.p.q + o["k"] + o[k] + keysof o.p;"#);
+        assert_round_trip(r#"o.p = 1; o[k] = 2; o.p["q"].r = 3;"#);
+        assert_round_trip(r#"o.m(1); o["m"](2); o[k](); f(1, g(2));"#);
+        assert_round_trip("res !a && b || c;");
+    }
+
+    #[test]
+    fn precedence_round_trips() {
+        assert_round_trip("res a - (b - c);");
+        assert_round_trip("res (a - b) - c;");
+        assert_round_trip("res -(a + b);");
+        assert_round_trip("res -a.b * c;");
+        assert_round_trip("res !(a && b);");
+        assert_round_trip("res (a || b) && c;");
+        assert_round_trip("res a || b && c;");
+        assert_round_trip("res a * (b + c) / (d / e);");
+        assert_round_trip("res a == (b == c);");
+        assert_round_trip("res (a < b) == (c >= d);");
+        assert_round_trip("res a != b + c;");
+        assert_round_trip("res --a;");
+        assert_round_trip("res (keysof o).length;");
+        assert_round_trip("({a: 1}).a;");
+    }
+
+    #[test]
+    fn comment_at_end_of_if_body_stays_in_if_body() {
+        let expected = "if a {\n    x;\n    // c1\n} else {\n    z;\n}\n";
+        assert_eq!(format("if a {\n  x;\n  // c1\n} else {\n  z;\n}\n"), expected);
+        assert_eq!(format("if a {\n  x;\n} // c1\nelse {\n  z;\n}\n"), expected);
+    }
+
+    #[test]
+    fn comment_before_else_if_stays_in_if_body() {
+        let expected = "if a {\n    x;\n    // c1\n} else if b {\n    z;\n}\n";
+        assert_eq!(format("if a {\n  x;\n}\n// c1\nelse if b {\n  z;\n}\n"), expected);
+    }
+
+    #[test]
+    fn comments_inside_expressions_keep_their_position() {
+        let code = "vl a = [1, // one\n2];\nvl  b=f(x, // arg\n  y); // after\nif a && // cond\n  b {\n  c;\n} else if c || // other\n  d {\n  e;\n}\n";
+        let expected = "vl a = [1, // one\n2];\nvl  b=f(x, // arg\n  y); // after\nif a && // cond\n  b {\n    c;\n} else if c || // other\n  d {\n    e;\n}\n";
+        assert_eq!(format(code), expected);
+        assert_eq!(format(expected), expected);
+    }
+}
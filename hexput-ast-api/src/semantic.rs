@@ -0,0 +1,285 @@
+use crate::ast_structs::{Block, Expression, Program, SourceLocation, Statement};
+use crate::visitor::{self, Visitor};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum SemanticError {
+    UndefinedVariable(String, SourceLocation),
+    UndefinedCallback(String, SourceLocation),
+    AssignmentBeforeDeclaration(String, SourceLocation),
+    Shadowing(String, SourceLocation, Option<SourceLocation>),
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticError::UndefinedVariable(name, loc) =>
+                write!(f, "Undefined variable: {} at line {}, column {}",
+                    name, loc.start_line, loc.start_column),
+            SemanticError::UndefinedCallback(name, loc) =>
+                write!(f, "Undefined callback or host function: {} at line {}, column {}",
+                    name, loc.start_line, loc.start_column),
+            SemanticError::AssignmentBeforeDeclaration(name, loc) =>
+                write!(f, "Assignment to {} before its declaration at line {}, column {}",
+                    name, loc.start_line, loc.start_column),
+            SemanticError::Shadowing(name, loc, Some(shadowed)) =>
+                write!(f, "Declaration of {} shadows the one at line {} at line {}, column {}",
+                    name, shadowed.start_line, loc.start_line, loc.start_column),
+            SemanticError::Shadowing(name, loc, None) =>
+                write!(f, "Declaration of {} shadows a host global at line {}, column {}",
+                    name, loc.start_line, loc.start_column),
+        }
+    }
+}
+
+impl SemanticError {
+    pub fn location(&self) -> SourceLocation {
+        match self {
+            SemanticError::UndefinedVariable(_, loc) |
+            SemanticError::UndefinedCallback(_, loc) |
+            SemanticError::AssignmentBeforeDeclaration(_, loc) |
+            SemanticError::Shadowing(_, loc, _) => *loc,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::UndefinedVariable(..) => "E3001",
+            SemanticError::UndefinedCallback(..) => "E3002",
+            SemanticError::AssignmentBeforeDeclaration(..) => "E3003",
+            SemanticError::Shadowing(..) => "E3004",
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SemanticError::UndefinedVariable(..) => "UndefinedVariable",
+            SemanticError::UndefinedCallback(..) => "UndefinedCallback",
+            SemanticError::AssignmentBeforeDeclaration(..) => "AssignmentBeforeDeclaration",
+            SemanticError::Shadowing(..) => "Shadowing",
+        }
+    }
+}
+
+pub fn analyze_scopes(program: &Program, host_globals: &[String]) -> Vec<SemanticError> {
+    let mut collector = CallbackCollector::default();
+    collector.visit_program(program);
+
+    let mut globals = Scope::new(&program.statements);
+    for name in host_globals {
+        globals.declared.insert(name.clone(), None);
+    }
+
+    let mut resolver = ScopeResolver {
+        globals,
+        frames: vec![Vec::new()],
+        host_globals: host_globals.iter().cloned().collect(),
+        callbacks: collector.names,
+        declared_callbacks: HashSet::new(),
+        errors: Vec::new(),
+    };
+    resolver.visit_program(program);
+    resolver.errors
+}
+
+#[derive(Default)]
+struct CallbackCollector {
+    names: HashSet<String>,
+}
+
+impl Visitor for CallbackCollector {
+    fn visit_statement(&mut self, statement: &Statement) {
+        if let Statement::CallbackDeclaration { name, .. } = statement {
+            self.names.insert(name.clone());
+        }
+        visitor::walk_statement(self, statement);
+    }
+}
+
+struct Scope {
+    declared: HashMap<String, Option<SourceLocation>>,
+    later: HashMap<String, SourceLocation>,
+}
+
+impl Scope {
+    fn new(statements: &[Statement]) -> Self {
+        let later = statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::VariableDeclaration { name, location, .. } => Some((name.clone(), *location)),
+                _ => None,
+            })
+            .collect();
+
+        Self { declared: HashMap::new(), later }
+    }
+}
+
+enum Resolution {
+    Found(Option<SourceLocation>),
+    DeclaredLater,
+    Missing,
+}
+
+struct ScopeResolver {
+    globals: Scope,
+    frames: Vec<Vec<Scope>>,
+    host_globals: HashSet<String>,
+    callbacks: HashSet<String>,
+    declared_callbacks: HashSet<String>,
+    errors: Vec<SemanticError>,
+}
+
+impl ScopeResolver {
+    fn in_callback(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    fn frame_mut(&mut self) -> &mut Vec<Scope> {
+        self.frames.last_mut().expect("resolver always has an active frame")
+    }
+
+    fn current_scope_mut(&mut self) -> &mut Scope {
+        let frame = self.frames.last_mut().expect("resolver always has an active frame");
+        match frame.last_mut() {
+            Some(scope) => scope,
+            None => &mut self.globals,
+        }
+    }
+
+    fn resolve(&self, name: &str) -> Resolution {
+        let mut declared_later = false;
+
+        for scope in self.frames.last().into_iter().flatten().rev() {
+            if let Some(location) = scope.declared.get(name) {
+                return Resolution::Found(*location);
+            }
+            declared_later |= scope.later.contains_key(name);
+        }
+
+        if let Some(location) = self.globals.declared.get(name) {
+            return Resolution::Found(*location);
+        }
+
+        if let Some(location) = self.globals.later.get(name) {
+            if self.in_callback() {
+                return Resolution::Found(Some(*location));
+            }
+            declared_later = true;
+        }
+
+        if declared_later { Resolution::DeclaredLater } else { Resolution::Missing }
+    }
+
+    fn declare(&mut self, name: &str, location: SourceLocation) {
+        let shadowed = match self.resolve(name) {
+            Resolution::Found(shadowed) => Some(shadowed),
+            _ => None,
+        };
+
+        let scope = self.current_scope_mut();
+        scope.later.remove(name);
+        let redeclared = matches!(scope.declared.insert(name.to_string(), Some(location)), Some(Some(_)));
+
+        if let (false, Some(shadowed)) = (redeclared, shadowed) {
+            self.errors.push(SemanticError::Shadowing(name.to_string(), location, shadowed));
+        }
+    }
+
+    fn check_variable(&mut self, name: &str, location: SourceLocation, assignment: bool) {
+        match self.resolve(name) {
+            Resolution::Found(_) => {},
+            Resolution::DeclaredLater if assignment => {
+                self.errors.push(SemanticError::AssignmentBeforeDeclaration(name.to_string(), location));
+            },
+            _ => self.errors.push(SemanticError::UndefinedVariable(name.to_string(), location)),
+        }
+    }
+
+    fn check_callback(&mut self, name: &str, location: SourceLocation, allow_host: bool) {
+        let callback = if self.in_callback() {
+            self.callbacks.contains(name)
+        } else {
+            self.declared_callbacks.contains(name)
+        };
+        let host = allow_host && self.host_globals.contains(name);
+
+        if !callback && !host {
+            self.errors.push(SemanticError::UndefinedCallback(name.to_string(), location));
+        }
+    }
+
+    fn visit_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+}
+
+impl Visitor for ScopeResolver {
+    fn visit_block(&mut self, block: &Block) {
+        self.frame_mut().push(Scope::new(&block.statements));
+        visitor::walk_block(self, block);
+        self.frame_mut().pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::VariableDeclaration { name, value, location } => {
+                self.visit_expression(value);
+                self.declare(name, *location);
+            },
+            Statement::CallbackDeclaration { name, params, body, location } => {
+                self.declared_callbacks.insert(name.clone());
+                self.frames.push(vec![Scope::new(&body.statements)]);
+                for param in params {
+                    self.declare(param, *location);
+                }
+                self.visit_statements(&body.statements);
+                self.frames.pop();
+            },
+            Statement::LoopStatement { variable, iterable, body, location } => {
+                self.visit_expression(iterable);
+                self.frame_mut().push(Scope::new(&body.statements));
+                self.declare(variable, *location);
+                self.visit_statements(&body.statements);
+                self.frame_mut().pop();
+            },
+            _ => visitor::walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match expression {
+            Expression::Identifier { name, location } => self.check_variable(name, *location, false),
+            Expression::AssignmentExpression { target, location, .. } => self.check_variable(target, *location, true),
+            Expression::CallExpression { callee, location, .. } => self.check_callback(callee, *location, true),
+            Expression::CallbackReference { name, location } => self.check_callback(name, *location, false),
+            _ => {},
+        }
+        visitor::walk_expression(self, expression);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feature_flags::FeatureFlags;
+
+    fn analyze(code: &str) -> Vec<SemanticError> {
+        let program = crate::process_code(code, FeatureFlags::all_enabled()).expect("test script parses");
+        analyze_scopes(&program, &[])
+    }
+
+    #[test]
+    fn bare_block_declarations_shadow_outer_variables() {
+        let errors = analyze("vl x = 1; { vl x = 2; } res x;");
+        assert!(matches!(errors.as_slice(), [SemanticError::Shadowing(name, _, Some(_))] if name == "x"));
+    }
+
+    #[test]
+    fn bare_block_declarations_are_not_visible_after_the_block() {
+        let errors = analyze("{ vl y = 2; } res y;");
+        assert!(matches!(errors.as_slice(), [SemanticError::UndefinedVariable(name, _)] if name == "y"));
+    }
+}
@@ -0,0 +1,237 @@
+use crate::ast_structs::{Block, Expression, Program, Property, Statement};
+
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_property(&mut self, property: &Property) {
+        walk_property(self, property);
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for statement in &program.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::VariableDeclaration { value, .. } => visitor.visit_expression(value),
+        Statement::ExpressionStatement { expression, .. } => visitor.visit_expression(expression),
+        Statement::IfStatement { condition, body, else_body, .. } => {
+            visitor.visit_expression(condition);
+            visitor.visit_block(body);
+            if let Some(else_body) = else_body {
+                visitor.visit_block(else_body);
+            }
+        },
+        Statement::Block { block, .. } => visitor.visit_block(block),
+        Statement::CallbackDeclaration { body, .. } => visitor.visit_block(body),
+        Statement::ReturnStatement { value, .. } => visitor.visit_expression(value),
+        Statement::LoopStatement { iterable, body, .. } => {
+            visitor.visit_expression(iterable);
+            visitor.visit_block(body);
+        },
+        Statement::EndStatement { .. } |
+        Statement::ContinueStatement { .. } |
+        Statement::ErrorStatement { .. } => {},
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::BinaryExpression { left, right, .. } |
+        Expression::LogicalExpression { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        },
+        Expression::UnaryExpression { operand, .. } => visitor.visit_expression(operand),
+        Expression::AssignmentExpression { value, .. } => visitor.visit_expression(value),
+        Expression::MemberAssignmentExpression { object, property_expr, value, .. } => {
+            visitor.visit_expression(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression(property_expr);
+            }
+            visitor.visit_expression(value);
+        },
+        Expression::CallExpression { arguments, .. } => {
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        },
+        Expression::MemberCallExpression { object, property_expr, arguments, .. } => {
+            visitor.visit_expression(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression(property_expr);
+            }
+            for argument in arguments {
+                visitor.visit_expression(argument);
+            }
+        },
+        Expression::ArrayExpression { elements, .. } => {
+            for element in elements {
+                visitor.visit_expression(element);
+            }
+        },
+        Expression::ObjectExpression { properties, .. } => {
+            for property in properties {
+                visitor.visit_property(property);
+            }
+        },
+        Expression::MemberExpression { object, property_expr, .. } => {
+            visitor.visit_expression(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression(property_expr);
+            }
+        },
+        Expression::KeysOfExpression { object, .. } => visitor.visit_expression(object),
+        Expression::StringLiteral { .. } |
+        Expression::NumberLiteral { .. } |
+        Expression::BooleanLiteral { .. } |
+        Expression::NullLiteral { .. } |
+        Expression::Identifier { .. } |
+        Expression::CallbackReference { .. } => {},
+    }
+}
+
+pub fn walk_property<V: Visitor + ?Sized>(visitor: &mut V, property: &Property) {
+    visitor.visit_expression(&property.value);
+}
+
+pub trait VisitorMut {
+    fn visit_program_mut(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_statement_mut(&mut self, statement: &mut Statement) {
+        walk_statement_mut(self, statement);
+    }
+
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    fn visit_property_mut(&mut self, property: &mut Property) {
+        walk_property_mut(self, property);
+    }
+}
+
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for statement in &mut program.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_block_mut<V: VisitorMut + ?Sized>(visitor: &mut V, block: &mut Block) {
+    for statement in &mut block.statements {
+        visitor.visit_statement_mut(statement);
+    }
+}
+
+pub fn walk_statement_mut<V: VisitorMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::VariableDeclaration { value, .. } => visitor.visit_expression_mut(value),
+        Statement::ExpressionStatement { expression, .. } => visitor.visit_expression_mut(expression),
+        Statement::IfStatement { condition, body, else_body, .. } => {
+            visitor.visit_expression_mut(condition);
+            visitor.visit_block_mut(body);
+            if let Some(else_body) = else_body {
+                visitor.visit_block_mut(else_body);
+            }
+        },
+        Statement::Block { block, .. } => visitor.visit_block_mut(block),
+        Statement::CallbackDeclaration { body, .. } => visitor.visit_block_mut(body),
+        Statement::ReturnStatement { value, .. } => visitor.visit_expression_mut(value),
+        Statement::LoopStatement { iterable, body, .. } => {
+            visitor.visit_expression_mut(iterable);
+            visitor.visit_block_mut(body);
+        },
+        Statement::EndStatement { .. } |
+        Statement::ContinueStatement { .. } |
+        Statement::ErrorStatement { .. } => {},
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::BinaryExpression { left, right, .. } |
+        Expression::LogicalExpression { left, right, .. } => {
+            visitor.visit_expression_mut(left);
+            visitor.visit_expression_mut(right);
+        },
+        Expression::UnaryExpression { operand, .. } => visitor.visit_expression_mut(operand),
+        Expression::AssignmentExpression { value, .. } => visitor.visit_expression_mut(value),
+        Expression::MemberAssignmentExpression { object, property_expr, value, .. } => {
+            visitor.visit_expression_mut(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression_mut(property_expr);
+            }
+            visitor.visit_expression_mut(value);
+        },
+        Expression::CallExpression { arguments, .. } => {
+            for argument in arguments {
+                visitor.visit_expression_mut(argument);
+            }
+        },
+        Expression::MemberCallExpression { object, property_expr, arguments, .. } => {
+            visitor.visit_expression_mut(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression_mut(property_expr);
+            }
+            for argument in arguments {
+                visitor.visit_expression_mut(argument);
+            }
+        },
+        Expression::ArrayExpression { elements, .. } => {
+            for element in elements {
+                visitor.visit_expression_mut(element);
+            }
+        },
+        Expression::ObjectExpression { properties, .. } => {
+            for property in properties {
+                visitor.visit_property_mut(property);
+            }
+        },
+        Expression::MemberExpression { object, property_expr, .. } => {
+            visitor.visit_expression_mut(object);
+            if let Some(property_expr) = property_expr {
+                visitor.visit_expression_mut(property_expr);
+            }
+        },
+        Expression::KeysOfExpression { object, .. } => visitor.visit_expression_mut(object),
+        Expression::StringLiteral { .. } |
+        Expression::NumberLiteral { .. } |
+        Expression::BooleanLiteral { .. } |
+        Expression::NullLiteral { .. } |
+        Expression::Identifier { .. } |
+        Expression::CallbackReference { .. } => {},
+    }
+}
+
+pub fn walk_property_mut<V: VisitorMut + ?Sized>(visitor: &mut V, property: &mut Property) {
+    visitor.visit_expression_mut(&mut property.value);
+}
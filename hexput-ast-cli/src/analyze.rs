@@ -0,0 +1,72 @@
+use clap::{Arg, ArgAction, Command};
+use hexput_ast_api::ast_structs::ColumnEncoding;
+use hexput_ast_api::feature_flags::FeatureFlags;
+use hexput_ast_api::manifest;
+use std::io::{self, Read};
+
+use crate::render;
+
+pub fn run(args: &[String]) -> i32 {
+    let matches = Command::new("analyze")
+        .about("Print the host functions, free variables and callbacks a script uses")
+        .arg(Arg::new("code")
+            .help("Code to analyze; reads stdin when omitted")
+            .action(ArgAction::Set))
+        .arg(Arg::new("minify")
+            .long("minify")
+            .help("Minify the output JSON (remove whitespace)")
+            .action(ArgAction::SetTrue))
+        .arg(Arg::new("error-format")
+            .long("error-format")
+            .help("Format of reported errors: json (default) or human")
+            .value_parser(["json", "human"])
+            .default_value("json")
+            .action(ArgAction::Set))
+        .no_binary_name(true)
+        .get_matches_from(args);
+
+    let minify = matches.get_flag("minify");
+    let human_errors = matches.get_one::<String>("error-format").is_some_and(|format| format == "human");
+
+    let code = match matches.get_one::<String>("code") {
+        Some(code) => code.clone(),
+        None => {
+            let mut code = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut code) {
+                eprintln!("error: could not read stdin: {}", e);
+                return 1;
+            }
+            code
+        }
+    };
+
+    match hexput_ast_api::process_code(&code, FeatureFlags::all_enabled()) {
+        Ok(program) => {
+            let manifest = manifest::extract_manifest(&program);
+            let json_result = if minify {
+                serde_json::to_string(&manifest)
+            } else {
+                serde_json::to_string_pretty(&manifest)
+            };
+
+            match json_result {
+                Ok(json) => {
+                    println!("{}", json);
+                    0
+                },
+                Err(e) => {
+                    eprintln!("Error serializing manifest to JSON: {}", e);
+                    1
+                }
+            }
+        }
+        Err(e) => {
+            if human_errors {
+                eprint!("{}", render::render_error(&e, &code, "<input>", ColumnEncoding::default()));
+            } else {
+                eprintln!("{}", hexput_ast_api::format_error_as_json(&e, minify));
+            }
+            1
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use clap::{Arg, ArgAction, Command};
+use hexput_ast_api::ast_structs::ColumnEncoding;
+use hexput_ast_api::feature_flags::FeatureFlags;
+use std::fs;
+use std::io::{self, Read};
+
+use crate::render;
+
+pub fn run(args: &[String]) -> i32 {
+    let matches = Command::new("fmt")
+        .about("Format source files in place")
+        .arg(Arg::new("files")
+            .help("Files to format; reads stdin and writes stdout when omitted")
+            .num_args(0..)
+            .action(ArgAction::Append))
+        .arg(Arg::new("check")
+            .long("check")
+            .help("Report unformatted files instead of rewriting them")
+            .action(ArgAction::SetTrue))
+        .no_binary_name(true)
+        .get_matches_from(args);
+
+    let check = matches.get_flag("check");
+    let files: Vec<&String> = matches.get_many::<String>("files").map(|files| files.collect()).unwrap_or_default();
+
+    if files.is_empty() {
+        return format_stdin(check);
+    }
+
+    let mut status = 0;
+    for file in files {
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("error: could not read {}: {}", file, e);
+                status = 1;
+                continue;
+            }
+        };
+
+        let Some(formatted) = format(&source, file) else {
+            status = 1;
+            continue;
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("{}", file);
+            status = 1;
+        } else if let Err(e) = fs::write(file, formatted) {
+            eprintln!("error: could not write {}: {}", file, e);
+            status = 1;
+        }
+    }
+
+    status
+}
+
+fn format_stdin(check: bool) -> i32 {
+    let mut source = String::new();
+    if let Err(e) = io::stdin().read_to_string(&mut source) {
+        eprintln!("error: could not read stdin: {}", e);
+        return 1;
+    }
+
+    let Some(formatted) = format(&source, "<stdin>") else {
+        return 1;
+    };
+
+    if check {
+        if formatted == source { 0 } else { println!("<stdin>"); 1 }
+    } else {
+        print!("{}", formatted);
+        0
+    }
+}
+
+fn format(source: &str, name: &str) -> Option<String> {
+    match hexput_ast_api::format_source(source, FeatureFlags::all_enabled()) {
+        Ok(formatted) => Some(formatted),
+        Err(e) => {
+            eprint!("{}", render::render_error(&e, source, name, ColumnEncoding::default()));
+            None
+        }
+    }
+}
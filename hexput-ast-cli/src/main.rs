@@ -1,9 +1,24 @@
+use hexput_ast_api::ast_structs::ColumnEncoding;
 use hexput_ast_api::feature_flags::FeatureFlags;
 use clap::{Arg, Command, ArgAction};
 use std::env;
 use std::process;
 
+mod analyze;
+mod fmt;
+mod render;
+
+const VALUE_OPTIONS: &[&str] = &["--error-format", "--column-encoding"];
+
 fn main() {
+    let args: Vec<String> = env::args().collect();
+    
+    match args.get(1).map(String::as_str) {
+        Some("fmt") => process::exit(fmt::run(&args[2..])),
+        Some("analyze") => process::exit(analyze::run(&args[2..])),
+        _ => {},
+    }
+    
     let matches = Command::new("ast-resolver-cli")
         .version("0.1.0")
         .about("AST resolver for a custom scripting language")
@@ -56,11 +71,19 @@ fn main() {
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("no-operators")
             .long("no-operators")
-            .help("Disable arithmetic operators (+, *, /)")
+            .help("Disable arithmetic operators (+, -, *, /)")
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("no-equality")
             .long("no-equality")
-            .help("Disable equality operator (==)")
+            .help("Disable equality operators (==, !=)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("no-comparisons")
+            .long("no-comparisons")
+            .help("Disable comparison operators (>, <, >=, <=)")
+            .action(clap::ArgAction::SetTrue))
+        .arg(Arg::new("no-logical-operators")
+            .long("no-logical-operators")
+            .help("Disable logical operators (&&, ||, !)")
             .action(clap::ArgAction::SetTrue))
         .arg(Arg::new("no-assignments")
             .long("no-assignments")
@@ -70,13 +93,23 @@ fn main() {
             .long("no-source-mapping")
             .help("Disable source location information in the output JSON")
             .action(ArgAction::SetTrue))
+        .arg(Arg::new("error-format")
+            .long("error-format")
+            .help("Format of reported errors: json (default) or human")
+            .value_parser(["json", "human"])
+            .default_value("json")
+            .action(ArgAction::Set))
+        .arg(Arg::new("column-encoding")
+            .long("column-encoding")
+            .help("Unit used for columns in source locations: bytes, chars (default) or utf16")
+            .value_parser(["bytes", "chars", "utf16"])
+            .default_value("chars")
+            .action(ArgAction::Set))
         .disable_help_flag(true)
         .disable_version_flag(true)
         .allow_external_subcommands(true)
         .get_matches();
 
-    let args: Vec<String> = env::args().collect();
-    
     let code = extract_code_from_args(&args);
     
     let feature_flags = create_feature_flags_from_cli_args(&matches);
@@ -85,8 +118,24 @@ fn main() {
     
     let include_source_mapping = !matches.get_flag("no-source-mapping");
     
-    match hexput_ast_api::process_code(&code, feature_flags) {
-        Ok(program) => {
+    let human_errors = matches.get_one::<String>("error-format").is_some_and(|format| format == "human");
+    
+    let column_encoding = match matches.get_one::<String>("column-encoding").map(String::as_str) {
+        Some("bytes") => ColumnEncoding::Bytes,
+        Some("utf16") => ColumnEncoding::Utf16,
+        _ => ColumnEncoding::Chars,
+    };
+    
+    match hexput_ast_api::process_code_with_warnings(&code, feature_flags, column_encoding) {
+        Ok((program, warnings)) => {
+            for warning in &warnings {
+                if human_errors {
+                    eprint!("{}", render::render_warning(warning, &code, "<input>", column_encoding));
+                } else {
+                    eprintln!("{}", hexput_ast_api::format_warning_as_json(warning, minify));
+                }
+            }
+            
             let json_result = if minify {
                 hexput_ast_api::to_json_string(&program, include_source_mapping)
             } else {
@@ -104,8 +153,12 @@ fn main() {
             }
         }
         Err(e) => {
-            let error_json = hexput_ast_api::format_error_as_json(&e, minify);
-            eprintln!("{}", error_json);
+            if human_errors {
+                eprint!("{}", render::render_error(&e, &code, "<input>", column_encoding));
+            } else {
+                let error_json = hexput_ast_api::format_error_as_json(&e, minify);
+                eprintln!("{}", error_json);
+            }
             process::exit(1);
         }
     }
@@ -121,8 +174,9 @@ fn extract_code_from_args(args: &[String]) -> String {
         }
     } else if args.len() > 1 {
         let first_non_flag = args.iter()
+            .enumerate()
             .skip(1)
-            .position(|arg| !arg.starts_with("--"))
+            .position(|(i, arg)| !arg.starts_with("--") && !VALUE_OPTIONS.contains(&args[i - 1].as_str()))
             .map(|pos| pos + 1);
             
         if let Some(pos) = first_non_flag {
@@ -150,5 +204,9 @@ fn create_feature_flags_from_cli_args(args: &clap::ArgMatches) -> FeatureFlags {
         allow_return_statements: !args.get_flag("no-return-statements"),
         allow_loop_control: !args.get_flag("no-loop-control"),
         allow_assignments: !args.get_flag("no-assignments"),
+        allow_logical_operators: !args.get_flag("no-logical-operators"),
+        allow_equality: !args.get_flag("no-equality"),
+        allow_arithmetic_operators: !args.get_flag("no-operators"),
+        allow_comparison_operators: !args.get_flag("no-comparisons"),
     }
 }
\ No newline at end of file
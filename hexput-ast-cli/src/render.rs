@@ -0,0 +1,71 @@
+use hexput_ast_api::ast_structs::{ColumnEncoding, LineIndex, Span};
+use hexput_ast_api::optimizer::OptimizerWarning;
+use hexput_ast_api::parser::ParseError;
+
+const FEATURE_FLAGS: &[(&str, &str)] = &[
+    ("Variable declarations", "--no-variable-declaration"),
+    ("Conditional statements", "--no-conditionals"),
+    ("Loop statements", "--no-loops"),
+    ("Loop control statements", "--no-loop-control"),
+    ("Callback declarations", "--no-callbacks"),
+    ("Return statements", "--no-return-statements"),
+    ("Object literals", "--no-object-constructions"),
+    ("Array literals", "--no-array-constructions"),
+    ("Object navigation", "--no-object-navigation"),
+    ("Object property assignment", "--no-object-navigation"),
+    ("Object keys operator", "--no-object-keys"),
+    ("Assignments", "--no-assignments"),
+    ("Logical operators", "--no-logical-operators"),
+    ("Equality operators", "--no-equality"),
+    ("Comparison operators", "--no-comparisons"),
+    ("Arithmetic operators", "--no-operators"),
+];
+
+pub fn render_error(error: &ParseError, source: &str, name: &str, encoding: ColumnEncoding) -> String {
+    let header = format!("error[{}]: {}", error.code(), error.description());
+    render(&header, error.location().span, hint_for(error), source, name, encoding)
+}
+
+pub fn render_warning(warning: &OptimizerWarning, source: &str, name: &str, encoding: ColumnEncoding) -> String {
+    let header = format!("warning[{}]: {}", warning.code(), warning.description());
+    render(&header, warning.location().span, None, source, name, encoding)
+}
+
+fn render(header: &str, span: Span, hint: Option<String>, source: &str, name: &str, encoding: ColumnEncoding) -> String {
+    let reported = LineIndex::with_encoding(source, encoding).location(span.start, span.end);
+    let location = LineIndex::new(source).location(span.start, span.end);
+    let lines: Vec<&str> = source.lines().collect();
+    let gutter = location.end_line.to_string().len();
+
+    let mut output = format!("{}\n", header);
+    output.push_str(&format!("{:gutter$}--> {}:{}:{}\n", "", name, reported.start_line, reported.start_column));
+    output.push_str(&format!("{:gutter$} |\n", ""));
+
+    for line_number in location.start_line..=location.end_line {
+        let line = lines.get(line_number - 1).copied().unwrap_or("");
+        let line_length = line.chars().count() + 1;
+
+        let start = if line_number == location.start_line { location.start_column } else { 1 };
+        let end = if line_number == location.end_line { location.end_column } else { line_length };
+        let width = end.saturating_sub(start).max(1);
+
+        output.push_str(&format!("{:>gutter$} | {}\n", line_number, line));
+        output.push_str(&format!("{:gutter$} | {}{}\n", "", " ".repeat(start - 1), "^".repeat(width)));
+    }
+
+    if let Some(hint) = hint {
+        output.push_str(&format!("{:gutter$} = hint: {}\n", "", hint));
+    }
+
+    output
+}
+
+fn hint_for(error: &ParseError) -> Option<String> {
+    match error {
+        ParseError::FeatureDisabled(feature, _) => FEATURE_FLAGS.iter()
+            .find(|(name, _)| feature.starts_with(name))
+            .map(|(_, flag)| format!("feature disabled by {}", flag)),
+        ParseError::EndOfInput(_) => Some("the input ended before the statement was complete".to_string()),
+        _ => None,
+    }
+}
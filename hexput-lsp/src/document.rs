@@ -0,0 +1,87 @@
+use hexput_ast_api::ast_structs::{ColumnEncoding, Program, SourceLocation, Span};
+use hexput_ast_api::feature_flags::FeatureFlags;
+use hexput_ast_api::lexer::{self, TokenWithSpan};
+use hexput_ast_api::optimizer::{self, OptimizerWarning};
+use hexput_ast_api::parallel;
+use hexput_ast_api::parser::ParseError;
+use lsp_types::{Position, Range};
+
+use crate::symbols::{self, Declaration};
+
+pub struct Document {
+    pub text: String,
+    pub line_starts: Vec<usize>,
+    pub program: Program,
+    pub errors: Vec<ParseError>,
+    pub warnings: Vec<OptimizerWarning>,
+    pub tokens: Vec<TokenWithSpan>,
+    pub declarations: Vec<Declaration>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let (program, errors) = hexput_ast_api::parse_code_with_diagnostics(&text, FeatureFlags::all_enabled(), ColumnEncoding::Utf16);
+        let warnings = if errors.is_empty() {
+            optimizer::optimize_ast_with_warnings(program.clone(), &parallel::create_runtime()).1
+        } else {
+            Vec::new()
+        };
+        let (tokens, _) = lexer::tokenize_with_errors(&text);
+        let declarations = symbols::collect_declarations(&program, &tokens);
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            text,
+            line_starts,
+            program,
+            errors,
+            warnings,
+            tokens,
+            declarations,
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let character = match self.text.get(line_start..offset) {
+            Some(text) => text.encode_utf16().count(),
+            None => offset - line_start,
+        };
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    pub fn full_range(&self) -> Range {
+        self.range(Span::new(0, self.text.len()))
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+
+        let mut units = 0;
+        for (index, c) in self.text[line_start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return line_start + index;
+            }
+            units += c.len_utf16();
+        }
+
+        self.text.len()
+    }
+}
+
+pub fn to_range(location: &SourceLocation) -> Range {
+    Range::new(
+        Position::new(location.start_line.saturating_sub(1) as u32, location.start_column.saturating_sub(1) as u32),
+        Position::new(location.end_line.saturating_sub(1) as u32, location.end_column.saturating_sub(1) as u32),
+    )
+}
@@ -0,0 +1,20 @@
+pub mod document;
+pub mod server;
+pub mod symbols;
+
+use std::error::Error;
+
+use lsp_server::Connection;
+
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(server::capabilities())?;
+    connection.initialize(capabilities)?;
+
+    server::Server::default().run(connection)?;
+
+    io_threads.join()?;
+
+    Ok(())
+}
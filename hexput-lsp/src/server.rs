@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use hexput_ast_api::ast_structs::Statement;
+use hexput_ast_api::feature_flags::FeatureFlags;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{DocumentSymbolRequest, Formatting, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DiagnosticTag, DocumentFormattingParams, DocumentSymbol, DocumentSymbolParams,
+    DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, Location, MarkupContent, MarkupKind, NumberOrString, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, SymbolKind, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+};
+
+use crate::document::{self, Document};
+use crate::symbols;
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    pub fn run(&mut self, connection: Connection) -> Result<(), Box<dyn Error + Sync + Send>> {
+        for message in &connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    connection.sender.send(Message::Response(response))?;
+                },
+                Message::Notification(notification) => {
+                    if let Some(published) = self.handle_notification(notification) {
+                        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), published);
+                        connection.sender.send(Message::Notification(notification))?;
+                    }
+                },
+                Message::Response(_) => {},
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => respond::<HoverRequest>(request, |params| self.hover(params)),
+            GotoDefinition::METHOD => respond::<GotoDefinition>(request, |params| self.definition(params)),
+            DocumentSymbolRequest::METHOD => respond::<DocumentSymbolRequest>(request, |params| self.document_symbols(params)),
+            Formatting::METHOD => respond::<Formatting>(request, |params| self.format(params)),
+            _ => Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Unknown method: {}", request.method)),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = parse_params::<DidOpenTextDocument>(notification)?;
+                let document = params.text_document;
+                Some(self.update(document.uri, document.text, Some(document.version)))
+            },
+            DidChangeTextDocument::METHOD => {
+                let params = parse_params::<DidChangeTextDocument>(notification)?;
+                let text = params.content_changes.into_iter().last()?.text;
+                Some(self.update(params.text_document.uri, text, Some(params.text_document.version)))
+            },
+            DidCloseTextDocument::METHOD => {
+                let params = parse_params::<DidCloseTextDocument>(notification)?;
+                self.documents.remove(&params.text_document.uri);
+                Some(PublishDiagnosticsParams::new(params.text_document.uri, Vec::new(), None))
+            },
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) -> PublishDiagnosticsParams {
+        let document = Document::new(text);
+        let diagnostics = document.errors.iter()
+            .map(|error| Diagnostic {
+                range: document::to_range(&error.location()),
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: Some(NumberOrString::String(error.code().to_string())),
+                source: Some("hexput".to_string()),
+                message: error.description(),
+                ..Diagnostic::default()
+            })
+            .chain(document.warnings.iter().map(|warning| Diagnostic {
+                range: document::to_range(&warning.location()),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(warning.code().to_string())),
+                source: Some("hexput".to_string()),
+                message: warning.description(),
+                tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                ..Diagnostic::default()
+            }))
+            .collect();
+        self.documents.insert(uri.clone(), document);
+        PublishDiagnosticsParams::new(uri, diagnostics, version)
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+        let (name, span) = symbols::identifier_at(&document.tokens, offset)?;
+        let declaration = symbols::resolve(&document.declarations, name, span.start)?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("```hexput\n{}\n```\n{} `{}`", declaration.detail, declaration.kind.label(), declaration.name),
+            }),
+            range: Some(document.range(span)),
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let offset = document.offset(position.position);
+        let (name, span) = symbols::identifier_at(&document.tokens, offset)?;
+        let declaration = symbols::resolve(&document.declarations, name, span.start)?;
+
+        Some(GotoDefinitionResponse::Scalar(Location::new(
+            position.text_document.uri,
+            document.range(declaration.span),
+        )))
+    }
+
+    fn document_symbols(&self, params: DocumentSymbolParams) -> Option<DocumentSymbolResponse> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        Some(DocumentSymbolResponse::Nested(callback_symbols(document, &document.program.statements)))
+    }
+
+    fn format(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let formatted = hexput_ast_api::format_source(&document.text, FeatureFlags::all_enabled()).ok()?;
+
+        if formatted == document.text {
+            return Some(Vec::new());
+        }
+        Some(vec![TextEdit::new(document.full_range(), formatted)])
+    }
+}
+
+#[allow(deprecated)]
+fn callback_symbols(document: &Document, statements: &[Statement]) -> Vec<DocumentSymbol> {
+    let mut result = Vec::new();
+
+    for statement in statements {
+        match statement {
+            Statement::CallbackDeclaration { name, params, body, location } => {
+                let selection = document.declarations.iter()
+                    .find(|declaration| declaration.kind == symbols::DeclarationKind::Callback
+                        && declaration.name == *name
+                        && declaration.span.start >= location.span.start)
+                    .map(|declaration| declaration.span)
+                    .unwrap_or(location.span);
+
+                result.push(DocumentSymbol {
+                    name: name.clone(),
+                    detail: Some(format!("({})", params.join(", "))),
+                    kind: SymbolKind::FUNCTION,
+                    tags: None,
+                    deprecated: None,
+                    range: document.range(location.span),
+                    selection_range: document.range(selection),
+                    children: Some(callback_symbols(document, &body.statements)),
+                });
+            },
+            Statement::IfStatement { body, else_body, .. } => {
+                result.extend(callback_symbols(document, &body.statements));
+                if let Some(else_body) = else_body {
+                    result.extend(callback_symbols(document, &else_body.statements));
+                }
+            },
+            Statement::Block { block, .. } | Statement::LoopStatement { body: block, .. } => {
+                result.extend(callback_symbols(document, &block.statements));
+            },
+            _ => {},
+        }
+    }
+
+    result
+}
+
+fn respond<R: lsp_types::request::Request>(request: Request, handler: impl FnOnce(R::Params) -> R::Result) -> Response {
+    match serde_json::from_value::<R::Params>(request.params) {
+        Ok(params) => Response::new_ok(request.id, handler(params)),
+        Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+    }
+}
+
+fn parse_params<N: lsp_types::notification::Notification>(notification: Notification) -> Option<N::Params> {
+    serde_json::from_value(notification.params).ok()
+}
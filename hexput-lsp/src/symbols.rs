@@ -0,0 +1,128 @@
+use hexput_ast_api::ast_structs::{Block, Program, Span, Statement};
+use hexput_ast_api::lexer::{Token, TokenWithSpan};
+use hexput_ast_api::printer;
+use hexput_ast_api::visitor::{self, Visitor};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationKind {
+    Variable,
+    Callback,
+    Parameter,
+    LoopVariable,
+}
+
+impl DeclarationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeclarationKind::Variable => "variable",
+            DeclarationKind::Callback => "callback",
+            DeclarationKind::Parameter => "parameter",
+            DeclarationKind::LoopVariable => "loop variable",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    pub name: String,
+    pub kind: DeclarationKind,
+    pub span: Span,
+    pub scope: Span,
+    pub detail: String,
+}
+
+pub fn collect_declarations(program: &Program, tokens: &[TokenWithSpan]) -> Vec<Declaration> {
+    let mut collector = DeclarationCollector {
+        tokens,
+        scopes: vec![Span::new(0, usize::MAX)],
+        declarations: Vec::new(),
+    };
+    collector.visit_program(program);
+    collector.declarations
+}
+
+pub fn resolve<'a>(declarations: &'a [Declaration], name: &str, offset: usize) -> Option<&'a Declaration> {
+    declarations.iter()
+        .filter(|declaration| declaration.name == name
+            && declaration.span.start <= offset
+            && declaration.scope.start <= offset
+            && offset <= declaration.scope.end)
+        .min_by_key(|declaration| (declaration.scope.end - declaration.scope.start, usize::MAX - declaration.span.start))
+}
+
+pub fn identifier_at(tokens: &[TokenWithSpan], offset: usize) -> Option<(&str, Span)> {
+    tokens.iter()
+        .filter(|token| token.span.start <= offset && offset <= token.span.end)
+        .find_map(|token| match &token.token {
+            Token::Identifier(name) => Some((name.as_str(), Span::new(token.span.start, token.span.end))),
+            _ => None,
+        })
+}
+
+struct DeclarationCollector<'a> {
+    tokens: &'a [TokenWithSpan],
+    scopes: Vec<Span>,
+    declarations: Vec<Declaration>,
+}
+
+impl DeclarationCollector<'_> {
+    fn name_span(&self, name: &str, from: usize) -> Span {
+        self.tokens.iter()
+            .filter(|token| token.span.start >= from)
+            .find(|token| matches!(&token.token, Token::Identifier(identifier) if identifier == name))
+            .map(|token| Span::new(token.span.start, token.span.end))
+            .unwrap_or(Span::new(from, from))
+    }
+
+    fn declare(&mut self, name: &str, kind: DeclarationKind, span: Span, scope: Span, detail: String) {
+        self.declarations.push(Declaration {
+            name: name.to_string(),
+            kind,
+            span,
+            scope,
+            detail,
+        });
+    }
+
+    fn current_scope(&self) -> Span {
+        self.scopes.last().copied().unwrap_or(Span::new(0, usize::MAX))
+    }
+}
+
+impl Visitor for DeclarationCollector<'_> {
+    fn visit_block(&mut self, block: &Block) {
+        self.scopes.push(block.location.span);
+        visitor::walk_block(self, block);
+        self.scopes.pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        let scope = self.current_scope();
+        match statement {
+            Statement::VariableDeclaration { name, value, location } => {
+                let span = self.name_span(name, location.span.start);
+                let detail = format!("vl {} = {}", name, printer::expression_to_source(value));
+                self.declare(name, DeclarationKind::Variable, span, scope, detail);
+            },
+            Statement::CallbackDeclaration { name, params, body, location } => {
+                let span = self.name_span(name, location.span.start);
+                let detail = format!("cb {}({})", name, params.join(", "));
+                self.declare(name, DeclarationKind::Callback, span, scope, detail.clone());
+
+                let mut from = span.end;
+                for param in params {
+                    let param_span = self.name_span(param, from);
+                    from = param_span.end;
+                    self.declare(param, DeclarationKind::Parameter, param_span, Span::new(param_span.start, body.location.span.end), detail.clone());
+                }
+            },
+            Statement::LoopStatement { variable, iterable, body, location } => {
+                let span = self.name_span(variable, location.span.start);
+                let detail = format!("loop {} in {}", variable, printer::expression_to_source(iterable));
+                self.declare(variable, DeclarationKind::LoopVariable, span, Span::new(span.start, body.location.span.end), detail);
+            },
+            _ => {},
+        }
+        visitor::walk_statement(self, statement);
+    }
+}
@@ -0,0 +1,83 @@
+use std::io::BufReader;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use lsp_server::{Message, Notification, Request, RequestId, Response};
+use serde_json::{json, Value};
+
+struct Client {
+    server: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Client {
+    fn start() -> Self {
+        let mut server = Command::new(env!("CARGO_BIN_EXE_hexput-lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("language server starts");
+        let stdin = server.stdin.take().unwrap();
+        let stdout = BufReader::new(server.stdout.take().unwrap());
+        Self { server, stdin, stdout }
+    }
+
+    fn send(&mut self, message: Message) {
+        message.write(&mut self.stdin).expect("message is written");
+    }
+
+    fn request(&mut self, id: i32, method: &str, params: Value) -> Response {
+        self.send(Message::Request(Request::new(RequestId::from(id), method.to_string(), params)));
+        match self.receive() {
+            Message::Response(response) if response.id == RequestId::from(id) => response,
+            other => panic!("expected a response to {}, got {:?}", method, other),
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(Message::Notification(Notification::new(method.to_string(), params)));
+    }
+
+    fn receive(&mut self) -> Message {
+        Message::read(&mut self.stdout)
+            .expect("message is read")
+            .expect("server keeps the connection open")
+    }
+}
+
+#[test]
+fn scripted_session() {
+    let mut client = Client::start();
+    let uri = "file:///tmp/demo.hx";
+    let text = "vl total = 1+2;\ncb add(x, y) {\n    res x + y;\n}\nvl r = add(total, 2);\nvl bad = ;\n";
+
+    let initialized = client.request(1, "initialize", json!({ "capabilities": {} }));
+    let capabilities = &initialized.result.unwrap()["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], json!(true));
+    assert_eq!(capabilities["documentSymbolProvider"], json!(true));
+    client.notify("initialized", json!({}));
+
+    client.notify("textDocument/didOpen", json!({
+        "textDocument": { "uri": uri, "languageId": "hexput", "version": 1, "text": text }
+    }));
+    let Message::Notification(published) = client.receive() else {
+        panic!("expected diagnostics to be published");
+    };
+    assert_eq!(published.method, "textDocument/publishDiagnostics");
+    let diagnostics = published.params["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], json!("E0001"));
+    assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 5, "character": 9 }));
+
+    let symbols = client.request(2, "textDocument/documentSymbol", json!({ "textDocument": { "uri": uri } }));
+    let symbols = symbols.result.unwrap();
+    assert_eq!(symbols[0]["name"], json!("add"));
+    assert_eq!(symbols[0]["detail"], json!("(x, y)"));
+    assert_eq!(symbols[0]["selectionRange"]["start"], json!({ "line": 1, "character": 3 }));
+
+    let shutdown = client.request(3, "shutdown", Value::Null);
+    assert!(shutdown.error.is_none());
+    client.notify("exit", Value::Null);
+
+    assert!(client.server.wait().unwrap().success());
+}
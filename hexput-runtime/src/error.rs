@@ -14,6 +14,9 @@ pub enum RuntimeError {
     #[error("AST parsing error: {0}")]
     AstParsingError(String),
 
+    #[error("Script execution error: {0}")]
+    ExecutionError(String),
+
     #[error("Invalid request format: {0}")]
     InvalidRequestFormat(String),
 
@@ -1,23 +1,113 @@
 use crate::error::RuntimeError;
+use crate::host::{Connection, RemoteHost};
 use crate::messages::{WebSocketRequest, WebSocketResponse};
+use hexput_ast_api::diagnostics::Diagnostic;
+use hexput_ast_api::interpreter::Interpreter;
+use hexput_ast_api::manifest;
 use serde_json::Value;
-use tracing::{debug, error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, error, info, warn};
 
-pub async fn handle_request(request_data: &str) -> Result<String, RuntimeError> {
+const EXECUTION_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+pub async fn handle_request(request_data: &str, connection: Connection) -> Result<Option<String>, RuntimeError> {
     let request: WebSocketRequest = serde_json::from_str(request_data)
         .map_err(|e| RuntimeError::InvalidRequestFormat(e.to_string()))?;
 
     debug!("Received request with ID: {}", request.id);
     debug!("Action: {}", request.action);
 
+    if request.action == "cancel" {
+        return handle_cancel_request(request, &connection).await;
+    }
+
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let cancelled = match connection.register_request(&request.id, interrupt.clone()) {
+        Some(receiver) => receiver,
+        None => {
+            let response = WebSocketResponse {
+                id: request.id.clone(),
+                success: false,
+                result: None,
+                error: Some(Diagnostic::new("E2001", "RequestAlreadyRunning", format!("A request with ID {} is already running", request.id))),
+                warnings: Vec::new(),
+            };
+            return Ok(Some(serde_json::to_string(&response)?));
+        }
+    };
+
+    let id = request.id.clone();
+    let timeout_ms = request.timeout_ms;
+
+    let timeout = async {
+        match timeout_ms {
+            Some(ms) => tokio::time::sleep(Duration::from_millis(ms)).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    let result = tokio::select! {
+        result = dispatch_request(request, connection.clone(), interrupt.clone()) => result.map(Some),
+        _ = cancelled => {
+            info!("Request {} was cancelled", id);
+            interrupt.store(true, Ordering::SeqCst);
+            serde_json::to_string(&interrupted_response(&id, "E2003", "Cancelled", "cancelled"))
+                .map(Some)
+                .map_err(RuntimeError::from)
+        }
+        _ = timeout => {
+            warn!("Request {} timed out", id);
+            interrupt.store(true, Ordering::SeqCst);
+            serde_json::to_string(&interrupted_response(&id, "E2004", "TimedOut", "timed_out"))
+                .map(Some)
+                .map_err(RuntimeError::from)
+        }
+    };
+
+    connection.finish_request(&id);
+    result
+}
+
+async fn handle_cancel_request(request: WebSocketRequest, connection: &Connection) -> Result<Option<String>, RuntimeError> {
+    if connection.cancel_request(&request.id) {
+        return Ok(None);
+    }
+
+    let response = WebSocketResponse {
+        id: request.id.clone(),
+        success: false,
+        result: None,
+        error: Some(Diagnostic::new("E2002", "UnknownRequest", format!("No active request with ID {}", request.id))),
+        warnings: Vec::new(),
+    };
+    Ok(Some(serde_json::to_string(&response)?))
+}
+
+fn interrupted_response(id: &str, code: &str, kind: &str, reason: &str) -> WebSocketResponse {
+    WebSocketResponse {
+        id: id.to_string(),
+        success: false,
+        result: None,
+        error: Some(Diagnostic::new(code, kind, reason)),
+        warnings: Vec::new(),
+    }
+}
+
+async fn dispatch_request(request: WebSocketRequest, connection: Connection, interrupt: Arc<AtomicBool>) -> Result<String, RuntimeError> {
     match request.action.as_str() {
         "parse" => handle_parse_request(request).await,
+        "execute" => handle_execute_request(request, connection, interrupt).await,
+        "analyze" => handle_analyze_request(request).await,
         _ => {
             let response = WebSocketResponse {
                 id: request.id,
                 success: false,
                 result: None,
-                error: Some(format!("Unknown action: {}", request.action)),
+                error: Some(Diagnostic::new("E2005", "UnknownAction", format!("Unknown action: {}", request.action))),
+                warnings: Vec::new(),
             };
             Ok(serde_json::to_string(&response)?)
         }
@@ -25,13 +115,18 @@ pub async fn handle_request(request_data: &str) -> Result<String, RuntimeError>
 }
 
 async fn handle_parse_request(request: WebSocketRequest) -> Result<String, RuntimeError> {
-    let code = &request.code;
+    let code = request.code.clone();
     let options = &request.options;
 
     let feature_flags = options.to_feature_flags();
+    let column_encoding = options.column_encoding;
+
+    let parsed = tokio::task::spawn_blocking(move || hexput_ast_api::process_code_with_warnings(&code, feature_flags, column_encoding))
+        .await
+        .map_err(|e| RuntimeError::AstParsingError(e.to_string()))?;
 
-    match hexput_ast_api::process_code(code, feature_flags) {
-        Ok(program) => {
+    match parsed {
+        Ok((program, warnings)) => {
             let result = if options.minify {
                 hexput_ast_api::to_json_string(&program, options.include_source_mapping)
             } else {
@@ -41,13 +136,14 @@ async fn handle_parse_request(request: WebSocketRequest) -> Result<String, Runti
             match result {
                 Ok(json_str) => {
                     let value: Value = serde_json::from_str(&json_str)
-                        .map_err(|e| RuntimeError::SerializationError(e))?;
+                        .map_err(RuntimeError::SerializationError)?;
 
                     let response = WebSocketResponse {
                         id: request.id.clone(),
                         success: true,
                         result: Some(value),
                         error: None,
+                        warnings: warnings.iter().map(Diagnostic::from).collect(),
                     };
 
                     info!("Successfully parsed AST for request: {}", request.id);
@@ -59,7 +155,8 @@ async fn handle_parse_request(request: WebSocketRequest) -> Result<String, Runti
                         id: request.id,
                         success: false,
                         result: None,
-                        error: Some(format!("Error serializing AST: {}", e)),
+                        error: Some(Diagnostic::new("E2006", "SerializationError", format!("Error serializing AST: {}", e))),
+                        warnings: Vec::new(),
                     };
                     Ok(serde_json::to_string(&response)?)
                 }
@@ -71,9 +168,101 @@ async fn handle_parse_request(request: WebSocketRequest) -> Result<String, Runti
                 id: request.id,
                 success: false,
                 result: None,
-                error: Some(format!("Error parsing AST: {}", e)),
+                error: Some(Diagnostic::from(&e)),
+                warnings: Vec::new(),
             };
             Ok(serde_json::to_string(&response)?)
         }
     }
 }
+
+async fn handle_analyze_request(request: WebSocketRequest) -> Result<String, RuntimeError> {
+    let code = request.code.clone();
+    let feature_flags = request.options.to_feature_flags();
+    let column_encoding = request.options.column_encoding;
+
+    let analyze = move || hexput_ast_api::process_code_with_encoding(&code, feature_flags, column_encoding)
+        .map(|program| manifest::extract_manifest(&program));
+
+    let analyzed = tokio::task::spawn_blocking(analyze)
+        .await
+        .map_err(|e| RuntimeError::AstParsingError(e.to_string()))?;
+
+    let response = match analyzed {
+        Ok(manifest) => {
+            info!("Successfully analyzed script for request: {}", request.id);
+            WebSocketResponse {
+                id: request.id,
+                success: true,
+                result: Some(serde_json::to_value(&manifest)?),
+                error: None,
+                warnings: Vec::new(),
+            }
+        }
+        Err(e) => {
+            error!("AST parsing error: {}", e);
+            WebSocketResponse {
+                id: request.id,
+                success: false,
+                result: None,
+                error: Some(Diagnostic::from(&e)),
+                warnings: Vec::new(),
+            }
+        }
+    };
+
+    Ok(serde_json::to_string(&response)?)
+}
+
+async fn handle_execute_request(request: WebSocketRequest, connection: Connection, interrupt: Arc<AtomicBool>) -> Result<String, RuntimeError> {
+    let code = request.code.clone();
+    let feature_flags = request.options.to_feature_flags();
+    let limits = request.options.to_execution_limits();
+    let column_encoding = request.options.column_encoding;
+    let mut host = RemoteHost::new(connection, request.id.clone(), interrupt.clone());
+
+    let (sender, receiver) = oneshot::channel();
+    std::thread::Builder::new()
+        .name(format!("hexput-execute-{}", request.id))
+        .stack_size(EXECUTION_STACK_SIZE)
+        .spawn(move || {
+            let outcome = hexput_ast_api::process_code_with_encoding(&code, feature_flags, column_encoding)
+                .map_err(|e| Box::new(Diagnostic::from(&e)))
+                .and_then(|program| {
+                    let mut interpreter = Interpreter::with_limits(&mut host, limits);
+                    interpreter.set_interrupt_flag(interrupt);
+                    interpreter.run(&program)
+                        .map_err(|e| Box::new(Diagnostic::from(&e)))
+                });
+            let _ = sender.send(outcome);
+        })?;
+
+    let outcome = receiver
+        .await
+        .map_err(|e| RuntimeError::ExecutionError(e.to_string()))?;
+
+    let response = match outcome {
+        Ok(value) => {
+            info!("Successfully executed script for request: {}", request.id);
+            WebSocketResponse {
+                id: request.id,
+                success: true,
+                result: Some(serde_json::to_value(&value)?),
+                error: None,
+                warnings: Vec::new(),
+            }
+        }
+        Err(e) => {
+            error!("Error executing script: {}", e.message);
+            WebSocketResponse {
+                id: request.id,
+                success: false,
+                result: None,
+                error: Some(*e),
+                warnings: Vec::new(),
+            }
+        }
+    };
+
+    Ok(serde_json::to_string(&response)?)
+}
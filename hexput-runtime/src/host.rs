@@ -0,0 +1,148 @@
+use crate::messages::{FunctionCallRequest, FunctionCallResponse};
+use hexput_ast_api::interpreter::{HostFunctions, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+pub struct PendingCall {
+    pub request_id: String,
+    pub sender: oneshot::Sender<FunctionCallResponse>,
+}
+
+pub struct ActiveRequest {
+    pub cancel: oneshot::Sender<()>,
+    pub interrupt: Arc<AtomicBool>,
+}
+
+pub type PendingCalls = Arc<Mutex<HashMap<String, PendingCall>>>;
+pub type ActiveRequests = Arc<Mutex<HashMap<String, ActiveRequest>>>;
+
+#[derive(Clone)]
+pub struct Connection {
+    pub outgoing: mpsc::UnboundedSender<Message>,
+    pub pending_calls: PendingCalls,
+    pub active_requests: ActiveRequests,
+}
+
+impl Connection {
+    pub fn new(outgoing: mpsc::UnboundedSender<Message>) -> Self {
+        Self {
+            outgoing,
+            pending_calls: Arc::new(Mutex::new(HashMap::new())),
+            active_requests: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn send(&self, text: String) -> bool {
+        self.outgoing.send(Message::Text(text)).is_ok()
+    }
+
+    pub fn resolve_call(&self, response: FunctionCallResponse) {
+        let call = self.pending_calls.lock().unwrap().remove(&response.id);
+        match call {
+            Some(call) => {
+                let _ = call.sender.send(response);
+            },
+            None => warn!("Received result for unknown function call: {}", response.id),
+        }
+    }
+
+    pub fn register_request(&self, request_id: &str, interrupt: Arc<AtomicBool>) -> Option<oneshot::Receiver<()>> {
+        let mut active_requests = self.active_requests.lock().unwrap();
+        if active_requests.contains_key(request_id) {
+            return None;
+        }
+
+        let (cancel, receiver) = oneshot::channel();
+        active_requests.insert(request_id.to_string(), ActiveRequest { cancel, interrupt });
+        Some(receiver)
+    }
+
+    pub fn finish_request(&self, request_id: &str) {
+        self.active_requests.lock().unwrap().remove(request_id);
+        self.pending_calls.lock().unwrap().retain(|_, call| call.request_id != request_id);
+    }
+
+    pub fn cancel_request(&self, request_id: &str) -> bool {
+        match self.active_requests.lock().unwrap().remove(request_id) {
+            Some(request) => {
+                request.interrupt.store(true, Ordering::SeqCst);
+                request.cancel.send(()).is_ok()
+            },
+            None => false,
+        }
+    }
+
+    pub fn close(&self) {
+        for (_, request) in self.active_requests.lock().unwrap().drain() {
+            request.interrupt.store(true, Ordering::SeqCst);
+        }
+        self.pending_calls.lock().unwrap().clear();
+    }
+}
+
+pub struct RemoteHost {
+    connection: Connection,
+    request_id: String,
+    interrupt: Arc<AtomicBool>,
+}
+
+impl RemoteHost {
+    pub fn new(connection: Connection, request_id: String, interrupt: Arc<AtomicBool>) -> Self {
+        Self { connection, request_id, interrupt }
+    }
+
+    fn call(&mut self, function_name: &str, object: Option<Value>, arguments: Vec<Value>) -> Result<Value, String> {
+        let call_id = Uuid::new_v4().to_string();
+        let request = FunctionCallRequest {
+            id: call_id.clone(),
+            request_id: self.request_id.clone(),
+            action: "function_call".to_string(),
+            function_name: function_name.to_string(),
+            object,
+            arguments,
+        };
+        let text = serde_json::to_string(&request).map_err(|e| e.to_string())?;
+
+        let (sender, receiver) = oneshot::channel();
+        self.connection.pending_calls.lock().unwrap().insert(call_id.clone(), PendingCall {
+            request_id: self.request_id.clone(),
+            sender,
+        });
+
+        if self.interrupt.load(Ordering::SeqCst) {
+            self.connection.pending_calls.lock().unwrap().remove(&call_id);
+            return Err("Request was interrupted".to_string());
+        }
+
+        debug!("Sending function call {} ({}) for request {}", call_id, function_name, self.request_id);
+        if !self.connection.send(text) {
+            self.connection.pending_calls.lock().unwrap().remove(&call_id);
+            return Err("Connection closed".to_string());
+        }
+
+        let response = receiver.blocking_recv().map_err(|_| "Connection closed".to_string())?;
+        if let Some(error) = response.error {
+            return Err(error);
+        }
+
+        match response.result {
+            Some(result) => serde_json::from_value(result).map_err(|e| e.to_string()),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+impl HostFunctions for RemoteHost {
+    fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, String> {
+        self.call(name, None, arguments)
+    }
+
+    fn call_method(&mut self, object: Value, method: &str, arguments: Vec<Value>) -> Result<Value, String> {
+        self.call(method, Some(object), arguments)
+    }
+}
@@ -1,5 +1,6 @@
 pub mod error;
 pub mod handler;
+pub mod host;
 pub mod messages;
 pub mod server;
 
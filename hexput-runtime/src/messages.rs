@@ -1,13 +1,23 @@
+use hexput_ast_api::ast_structs::ColumnEncoding;
+use hexput_ast_api::diagnostics::Diagnostic;
 use hexput_ast_api::feature_flags::FeatureFlags;
+use hexput_ast_api::interpreter::{ExecutionLimits, Value};
 use serde::{Deserialize, Serialize};
 
+const DEFAULT_MAX_INSTRUCTIONS: u64 = 10_000_000;
+const DEFAULT_MAX_LOOP_ITERATIONS: u64 = 1_000_000;
+const DEFAULT_MAX_MEMORY: usize = 64 * 1024 * 1024;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WebSocketRequest {
     pub id: String,
     pub action: String,
+    #[serde(default)]
     pub code: String,
     #[serde(default)]
     pub options: AstParserOptions,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -17,6 +27,29 @@ pub struct WebSocketResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionCallRequest {
+    pub id: String,
+    pub request_id: String,
+    pub action: String,
+    pub function_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<Value>,
+    pub arguments: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunctionCallResponse {
+    pub id: String,
+    pub action: String,
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default)]
     pub error: Option<String>,
 }
 
@@ -37,6 +70,16 @@ pub struct AstParserOptions {
     pub no_operators: bool,
     pub no_equality: bool,
     pub no_assignments: bool,
+    #[serde(default)]
+    pub no_logical_operators: bool,
+    #[serde(default)]
+    pub no_comparisons: bool,
+    #[serde(default)]
+    pub column_encoding: ColumnEncoding,
+    pub max_instructions: Option<u64>,
+    pub max_loop_iterations: Option<u64>,
+    pub max_call_depth: Option<usize>,
+    pub max_memory: Option<usize>,
 }
 
 impl AstParserOptions {
@@ -53,6 +96,20 @@ impl AstParserOptions {
             allow_return_statements: !self.no_return_statements,
             allow_loop_control: !self.no_loop_control,
             allow_assignments: !self.no_assignments,
+            allow_logical_operators: !self.no_logical_operators,
+            allow_equality: !self.no_equality,
+            allow_arithmetic_operators: !self.no_operators,
+            allow_comparison_operators: !self.no_comparisons,
+        }
+    }
+
+    pub fn to_execution_limits(&self) -> ExecutionLimits {
+        let defaults = ExecutionLimits::default();
+        ExecutionLimits {
+            max_instructions: self.max_instructions.or(Some(DEFAULT_MAX_INSTRUCTIONS)),
+            max_loop_iterations: self.max_loop_iterations.or(Some(DEFAULT_MAX_LOOP_ITERATIONS)),
+            max_call_depth: self.max_call_depth.or(defaults.max_call_depth),
+            max_memory: self.max_memory.or(Some(DEFAULT_MAX_MEMORY)),
         }
     }
 }
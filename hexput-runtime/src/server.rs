@@ -1,10 +1,13 @@
 use crate::error::RuntimeError;
 use crate::handler::handle_request;
+use crate::host::Connection;
+use crate::messages::FunctionCallResponse;
+use hexput_ast_api::diagnostics::Diagnostic;
 use futures_util::{SinkExt, StreamExt};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::tungstenite::Message;
 use tracing::{error, info};
 
@@ -53,29 +56,49 @@ async fn handle_connection(stream: TcpStream, peer_addr: SocketAddr) -> Result<(
     info!("WebSocket connection established with: {}", peer_addr);
 
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let (outgoing, mut outgoing_receiver) = mpsc::unbounded_channel::<Message>();
+    let connection = Connection::new(outgoing);
+
+    let writer = tokio::spawn(async move {
+        while let Some(message) = outgoing_receiver.recv().await {
+            if let Err(e) = ws_sender.send(message).await {
+                error!("Error sending message to {}: {}", peer_addr, e);
+                break;
+            }
+        }
+    });
 
     while let Some(msg) = ws_receiver.next().await {
         match msg {
             Ok(Message::Text(text)) => {
                 info!("Received text message from {}", peer_addr);
 
-                let response = match handle_request(&text).await {
-                    Ok(resp) => Message::Text(resp),
-                    Err(e) => {
-                        error!("Error processing request: {}", e);
-                        Message::Text(format!(
-                            "{{\"error\":\"Internal server error: {}\"}}",
-                            e.to_string().replace('"', "\\\"")
-                        ))
+                if let Ok(call_response) = serde_json::from_str::<FunctionCallResponse>(&text) {
+                    if call_response.action == "function_result" {
+                        connection.resolve_call(call_response);
+                        continue;
                     }
-                };
-
-                if let Err(e) = ws_sender.send(response).await {
-                    error!("Error sending response to {}: {}", peer_addr, e);
                 }
+
+                let connection = connection.clone();
+                tokio::spawn(async move {
+                    let response = match handle_request(&text, connection.clone()).await {
+                        Ok(Some(resp)) => resp,
+                        Ok(None) => return,
+                        Err(e) => {
+                            error!("Error processing request: {}", e);
+                            let diagnostic = Diagnostic::new("E2000", "InternalError", format!("Internal server error: {}", e));
+                            serde_json::json!({ "error": diagnostic }).to_string()
+                        }
+                    };
+
+                    if !connection.send(response) {
+                        error!("Error sending response to {}: connection closed", peer_addr);
+                    }
+                });
             }
             Ok(Message::Ping(data)) => {
-                if let Err(e) = ws_sender.send(Message::Pong(data)).await {
+                if let Err(e) = connection.outgoing.send(Message::Pong(data)) {
                     error!("Error sending pong to {}: {}", peer_addr, e);
                 }
             }
@@ -92,5 +115,7 @@ async fn handle_connection(stream: TcpStream, peer_addr: SocketAddr) -> Result<(
     }
 
     info!("Closing connection with: {}", peer_addr);
+    connection.close();
+    writer.abort();
     Ok(())
 }